@@ -1,9 +1,59 @@
+extern crate cc;
+
+use std::env;
+use std::fs::read_dir;
+use std::path::Path;
+
+/// Links a prebuilt `newuoa` instead of the vendored sources under the `system` feature or
+/// when `NEWUOA_LIB_DIR` is set. `NEWUOA_STATIC=1` links it statically with the C++ runtime.
 fn main() {
-    use std::path::Path;
-    use std::process::Command;
+    println!("cargo:rerun-if-env-changed=NEWUOA_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=NEWUOA_STATIC");
+    let lib_dir = env::var("NEWUOA_LIB_DIR").ok();
+    if lib_dir.is_some() || env::var_os("CARGO_FEATURE_SYSTEM").is_some() {
+        link_system(lib_dir);
+    } else {
+        build_vendored();
+    }
+}
+
+fn link_system(lib_dir: Option<String>) {
+    if let Some(dir) = lib_dir {
+        println!("cargo:rustc-link-search=native={}", dir);
+    }
+    if env::var("NEWUOA_STATIC").is_ok_and(|v| v == "1") {
+        println!("cargo:rustc-link-lib=static=newuoa");
+        let target = env::var("TARGET").unwrap();
+        if target.contains("apple") || target.contains("freebsd") {
+            println!("cargo:rustc-link-lib=c++");
+        } else if !target.contains("msvc") {
+            println!("cargo:rustc-link-lib=stdc++");
+        }
+    } else {
+        println!("cargo:rustc-link-lib=newuoa");
+    }
+}
+
+fn build_vendored() {
     let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("newuoa-cpp");
-    Command::new("cmake").args(&["."]).current_dir(&dir).status().unwrap();
-    Command::new("make").current_dir(&dir).status().unwrap();
-    println!("cargo:rustc-link-search=native={}", dir.join("lib").display());
-    println!("cargo:rustc-link-lib=static=newuoa");
+    let sources = read_dir(dir.join("src")).expect("newuoa-cpp submodule is not checked out");
+    let mut build = cc::Build::new();
+    build.cpp(true).include(dir.join("include"));
+    if build.get_compiler().is_like_msvc() {
+        // Match the CRT the Rust code links with, a mismatch fails at link time.
+        let static_crt = env::var("CARGO_CFG_TARGET_FEATURE")
+            .is_ok_and(|v| v.split(',').any(|f| f == "crt-static"));
+        build.flag("/EHsc").flag("/std:c++14").static_crt(static_crt);
+    } else {
+        build.flag_if_supported("-std=c++14");
+    }
+    for entry in sources {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|v| v == "cpp") {
+            println!("cargo:rerun-if-changed={}", path.display());
+            build.file(path);
+        }
+    }
+    println!("cargo:rerun-if-changed={}", dir.join("include").display());
+    build.compile("newuoa");
 }
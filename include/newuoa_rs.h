@@ -0,0 +1,56 @@
+/* Built with `cargo rustc --release --lib --features capi --crate-type cdylib`. */
+
+#ifndef NEWUOA_RS_H
+#define NEWUOA_RS_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define NEWUOA_RS_OK 0
+#define NEWUOA_RS_NULL_POINTER -1
+#define NEWUOA_RS_DIMENSION_TOO_SMALL -2
+#define NEWUOA_RS_INVALID_INTERPOLATION_CONDITIONS -3
+#define NEWUOA_RS_RADIUS_ORDER_INVALID -4
+#define NEWUOA_RS_INVALID_ARGUMENT -5
+
+enum newuoa_rs_termination_reason {
+    NEWUOA_RS_CONVERGED,
+    NEWUOA_RS_MAX_FUNCTION_CALLS_REACHED,
+    NEWUOA_RS_STOPPED_BY_USER,
+    NEWUOA_RS_TIME_LIMIT,
+    NEWUOA_RS_TARGET_REACHED,
+    NEWUOA_RS_FUNCTION_TOLERANCE_REACHED,
+    NEWUOA_RS_NON_FINITE_OBJECTIVE,
+    NEWUOA_RS_OBJECTIVE_FAILED
+};
+
+typedef double (*newuoa_rs_function)(void *data, size_t n, const double *x);
+
+/* Zero or negative fields take the defaults of minimize::Options. */
+typedef struct {
+    size_t number_of_interpolation_conditions;
+    double initial_trust_region_radius;
+    double final_trust_region_radius;
+    size_t max_function_calls_count;
+} newuoa_rs_options;
+
+typedef struct {
+    double value;
+    size_t function_calls_count;
+    double final_trust_region_radius;
+    int termination_reason;
+} newuoa_rs_result;
+
+/* Minimizes function from the n values of x, which receives the best point found.
+ * options and result may be NULL. Returns NEWUOA_RS_OK or a negative error code. */
+int newuoa_rs_minimize(newuoa_rs_function function, void *data, size_t n, double *x,
+    const newuoa_rs_options *options, newuoa_rs_result *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
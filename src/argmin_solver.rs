@@ -0,0 +1,85 @@
+use argmin::core::{ArgminError, CostFunction, Error, IterState, Problem, Solver, TerminationReason,
+    TerminationStatus, KV};
+
+use Newuoa;
+use ask_tell::AskTell;
+
+type NewuoaState = IterState<Vec<f64>, (), (), (), (), f64>;
+
+/// NEWUOA as an argmin solver: every argmin iteration is one objective evaluation, run
+/// through `AskTell`. The initial parameter vector of the state is the start point and the
+/// solver settings, including the budget, come from the wrapped `Newuoa`.
+pub struct NewuoaSolver {
+    solver: Newuoa,
+    ask_tell: Option<AskTell>,
+}
+
+impl NewuoaSolver {
+    /// The observer of `solver` is not used, argmin has its own observers.
+    pub fn new(solver: &Newuoa) -> NewuoaSolver {
+        NewuoaSolver {solver: solver.detached(), ask_tell: None}
+    }
+}
+
+impl<O> Solver<O, NewuoaState> for NewuoaSolver
+        where O: CostFunction<Param = Vec<f64>, Output = f64> {
+    const NAME: &'static str = "NEWUOA";
+
+    fn init(&mut self, _problem: &mut Problem<O>, state: NewuoaState)
+            -> Result<(NewuoaState, Option<KV>), Error> {
+        let param = state.get_param().cloned().ok_or_else(|| ArgminError::NotInitialized {
+            text: "NEWUOA requires an initial parameter vector".to_string(),
+        })?;
+        self.ask_tell = Some(self.solver.ask_tell(&param)?);
+        Ok((state, None))
+    }
+
+    fn next_iter(&mut self, problem: &mut Problem<O>, state: NewuoaState)
+            -> Result<(NewuoaState, Option<KV>), Error> {
+        let ask_tell = match self.ask_tell {
+            Some(ref mut v) => v,
+            None => return Ok((state, None)),
+        };
+        let param = match ask_tell.ask() {
+            Some(x) => x.to_vec(),
+            None => return Ok((state, None)),
+        };
+        let cost = problem.cost(&param)?;
+        ask_tell.tell(cost);
+        Ok((state.param(param).cost(cost), None))
+    }
+
+    fn terminate(&mut self, _state: &NewuoaState) -> TerminationStatus {
+        if self.ask_tell.as_mut().is_some_and(|v| v.ask().is_none()) {
+            TerminationStatus::Terminated(TerminationReason::SolverConverged)
+        } else {
+            TerminationStatus::NotTerminated
+        }
+    }
+}
+
+#[cfg(test)]
+struct Sphere;
+
+#[cfg(test)]
+impl CostFunction for Sphere {
+    type Param = Vec<f64>;
+    type Output = f64;
+
+    fn cost(&self, param: &Vec<f64>) -> Result<f64, Error> {
+        Ok(param.iter().map(|v| v*v).sum())
+    }
+}
+
+#[test]
+fn test_newuoa_solver_should_run_until_budget_is_spent() {
+    let mut solver = NewuoaSolver::new(Newuoa::new().max_function_calls_count(10));
+    let mut problem = Problem::new(Sphere);
+    let (mut state, _) = solver.init(&mut problem, IterState::new().param(vec![1.0, 1.0])).unwrap();
+    let mut iterations_count = 0;
+    while Solver::<Sphere, _>::terminate(&mut solver, &state) == TerminationStatus::NotTerminated {
+        state = solver.next_iter(&mut problem, state).unwrap().0;
+        iterations_count += 1;
+    }
+    assert_eq!(iterations_count, 10);
+}
@@ -0,0 +1,31 @@
+use ndarray::{ArrayView1, ArrayViewMut1};
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+impl Newuoa {
+    /// `perform_mut` for ndarray users. The objective gets a view of the native point
+    /// without copying, and `values` may be a non-contiguous view.
+    pub fn perform_array<F>(&mut self, mut values: ArrayViewMut1<f64>, function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(ArrayView1<f64>) -> f64 {
+        let mut buffer = values.to_vec();
+        let result = self.perform_mut(&mut buffer, &mut |x: &[f64]| function(ArrayView1::from(x)))?;
+        for (v, b) in values.iter_mut().zip(buffer.iter()) {
+            *v = *b;
+        }
+        Ok(result)
+    }
+}
+
+#[test]
+fn test_perform_array_should_write_solution_back() {
+    use ndarray::Array1;
+    let mut values = Array1::from(vec![1.0, 1.0]);
+    let result = Newuoa::new()
+        .max_function_calls_count(20)
+        .perform_array(values.view_mut(), &mut |x: ArrayView1<f64>| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(values.to_vec(), result.solution);
+}
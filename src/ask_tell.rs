@@ -0,0 +1,169 @@
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::task::{Context, Poll};
+use std::thread::{spawn, JoinHandle};
+
+use Newuoa;
+use error::NewuoaError;
+use metadata::RunMetadata;
+use result::OptimizationResult;
+use wakeup::Wakeup;
+
+/// Inverts control of a run: the native routine works on a background thread and each
+/// point it wants evaluated is handed out by `ask`, with the value returned by `tell`.
+/// Dropping an unfinished run answers the remaining requests with the last told value,
+/// which flattens the model without making a new incumbent, and waits for the native
+/// routine to return.
+pub struct AskTell {
+    requests: Option<Receiver<Vec<f64>>>,
+    replies: Option<Sender<f64>>,
+    worker: Option<JoinHandle<Result<OptimizationResult, NewuoaError>>>,
+    pending: Option<Vec<f64>>,
+    result: Option<OptimizationResult>,
+    metadata: RunMetadata,
+    wakeup: Wakeup,
+}
+
+impl AskTell {
+    pub fn new(mut solver: Newuoa, values: Vec<f64>) -> AskTell {
+        let metadata = RunMetadata::new(solver.settings());
+        let (request_sender, requests) = channel();
+        let (replies, reply_receiver) = channel();
+        let wakeup = Wakeup::default();
+        let worker_wakeup = wakeup.clone();
+        let worker = spawn(move || {
+            let mut values = values;
+            let mut last_value = 0.0;
+            let result = solver.perform_mut(&mut values, &mut |x: &[f64]| -> f64 {
+                if request_sender.send(x.to_vec()).is_ok() {
+                    worker_wakeup.wake();
+                    if let Ok(value) = reply_receiver.recv() {
+                        last_value = value;
+                    }
+                }
+                last_value
+            });
+            drop(request_sender);
+            worker_wakeup.wake();
+            result
+        });
+        AskTell {
+            requests: Some(requests),
+            replies: Some(replies),
+            worker: Some(worker),
+            pending: None,
+            result: None,
+            metadata,
+            wakeup,
+        }
+    }
+
+    /// Next point to evaluate, or `None` once the run is finished. Asking again before
+    /// telling returns the same point.
+    pub fn ask(&mut self) -> Option<&[f64]> {
+        if self.pending.is_none() && self.result.is_none() {
+            match self.requests.as_ref().map(|v| v.recv()) {
+                Some(Ok(x)) => self.pending = Some(x),
+                _ => self.finish(),
+            }
+        }
+        self.pending.as_ref().map(|v| &v[..])
+    }
+
+    /// `ask` for asynchronous callers: pending until the background thread has the next
+    /// point, which wakes the task registered in `context`.
+    pub fn poll_ask(&mut self, context: &mut Context) -> Poll<Option<&[f64]>> {
+        if self.pending.is_none() && self.result.is_none() && self.worker.is_some() {
+            let received = match self.try_receive() {
+                Err(TryRecvError::Empty) => {
+                    self.wakeup.register(context.waker());
+                    self.try_receive()
+                }
+                other => other,
+            };
+            match received {
+                Ok(x) => self.pending = Some(x),
+                Err(TryRecvError::Empty) => return Poll::Pending,
+                Err(TryRecvError::Disconnected) => self.finish(),
+            }
+        }
+        Poll::Ready(self.pending.as_ref().map(|v| &v[..]))
+    }
+
+    pub fn tell(&mut self, value: f64) {
+        assert!(self.pending.take().is_some());
+        if let Some(ref replies) = self.replies {
+            let _ = replies.send(value);
+        }
+    }
+
+    /// Available once `ask` returned `None`, unless the solver settings were invalid.
+    /// `Newuoa::ask_tell` validates them before starting.
+    pub fn result(&self) -> Option<&OptimizationResult> {
+        self.result.as_ref()
+    }
+
+    pub fn metadata(&self) -> &RunMetadata {
+        &self.metadata
+    }
+
+    fn try_receive(&self) -> Result<Vec<f64>, TryRecvError> {
+        self.requests.as_ref().map_or(Err(TryRecvError::Disconnected), |v| v.try_recv())
+    }
+
+    fn finish(&mut self) {
+        self.requests = None;
+        self.replies = None;
+        if let Some(worker) = self.worker.take() {
+            match worker.join() {
+                Ok(result) => {
+                    self.result = result.ok();
+                    self.metadata.finish();
+                }
+                Err(error) => ::std::panic::resume_unwind(error),
+            }
+        }
+    }
+}
+
+impl Drop for AskTell {
+    fn drop(&mut self) {
+        self.pending = None;
+        self.requests = None;
+        self.replies = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[test]
+fn test_ask_tell_should_match_perform_mut() {
+    let function = |x: &[f64]| (x[0] - 1.0).powi(2) + 3.0*(x[1] + 0.5).powi(2);
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(40);
+    let mut values = [0.0, 0.0];
+    let expected = solver.perform_mut(&mut values, &mut |x: &[f64]| function(x)).unwrap();
+    let mut ask_tell = solver.ask_tell(&[0.0, 0.0]).unwrap();
+    let mut calls_count = 0;
+    while let Some(x) = ask_tell.ask().map(|v| v.to_vec()) {
+        assert_eq!(ask_tell.ask(), Some(&x[..]));
+        ask_tell.tell(function(&x));
+        calls_count += 1;
+    }
+    let result = ask_tell.result().unwrap();
+    assert_eq!(calls_count, expected.function_calls_count);
+    assert_eq!(result.solution, expected.solution);
+    assert_eq!(result.value, expected.value);
+    assert_eq!(result.termination_reason, expected.termination_reason);
+}
+
+#[test]
+fn test_drop_should_abandon_unfinished_run() {
+    let mut ask_tell = Newuoa::new().max_function_calls_count(100).ask_tell(&[1.0, 1.0]).unwrap();
+    for _ in 0..3 {
+        let value = ask_tell.ask().map(|x| x[0]*x[0] + x[1]*x[1]).unwrap();
+        ask_tell.tell(value);
+    }
+    assert!(ask_tell.ask().is_some());
+    drop(ask_tell);
+}
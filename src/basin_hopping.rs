@@ -0,0 +1,107 @@
+use Newuoa;
+use error::NewuoaError;
+use random::Random;
+use result::OptimizationResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BasinHoppingReport {
+    /// Best local minimum found, its function calls count is the one of its own run.
+    pub result: OptimizationResult,
+    pub accepted_count: usize,
+    pub function_calls_count: usize,
+}
+
+/// Basin hopping: jumps from the current local minimum by a seeded uniform step of up to
+/// `step_size` per coordinate, minimizes locally with the solver and accepts the new
+/// minimum by the Metropolis criterion at `temperature`.
+pub struct BasinHopping {
+    hops_count: usize,
+    temperature: f64,
+    step_size: f64,
+    seed: u64,
+}
+
+impl Default for BasinHopping {
+    fn default() -> BasinHopping {
+        BasinHopping::new()
+    }
+}
+
+impl BasinHopping {
+    pub fn new() -> BasinHopping {
+        BasinHopping {
+            hops_count: 100,
+            temperature: 1.0,
+            step_size: 0.5,
+            seed: 0,
+        }
+    }
+
+    pub fn hops_count(&mut self, value: usize) -> &mut Self {
+        self.hops_count = value;
+        self
+    }
+
+    pub fn temperature(&mut self, value: f64) -> &mut Self {
+        assert!(value >= 0.0);
+        self.temperature = value;
+        self
+    }
+
+    pub fn step_size(&mut self, value: f64) -> &mut Self {
+        assert!(value > 0.0);
+        self.step_size = value;
+        self
+    }
+
+    pub fn seed(&mut self, value: u64) -> &mut Self {
+        self.seed = value;
+        self
+    }
+
+    /// Every local minimization uses the budget configured on `solver`.
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [f64], function: &mut F)
+            -> Result<BasinHoppingReport, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let mut random = Random::new(self.seed);
+        let mut current = solver.perform_mut(values, function)?;
+        let mut function_calls_count = current.function_calls_count;
+        let mut best = current.clone();
+        let mut accepted_count = 0;
+        let mut start = values.to_vec();
+        for _ in 0..self.hops_count {
+            for (v, c) in start.iter_mut().zip(current.solution.iter()) {
+                *v = c + self.step_size*(2.0*random.next_f64() - 1.0);
+            }
+            let candidate = solver.perform_mut(&mut start, function)?;
+            function_calls_count += candidate.function_calls_count;
+            let accepted = candidate.value < current.value
+                || self.temperature > 0.0
+                    && random.next_f64() < (-(candidate.value - current.value)/self.temperature).exp();
+            if candidate.value < best.value {
+                best = candidate.clone();
+            }
+            if accepted {
+                accepted_count += 1;
+                current = candidate;
+            }
+        }
+        values[..best.solution.len()].copy_from_slice(&best.solution);
+        Ok(BasinHoppingReport {result: best, accepted_count, function_calls_count})
+    }
+}
+
+#[test]
+fn test_basin_hopping_should_not_lose_best_minimum() {
+    let mut values = [3.0, 0.0];
+    let mut function = |x: &[f64]| (x[0]*x[0] - 4.0).powi(2) + x[0] + x[1]*x[1];
+    let report = BasinHopping::new()
+        .hops_count(5)
+        .step_size(3.0)
+        .seed(5)
+        .perform_mut(Newuoa::new().max_function_calls_count(40), &mut values, &mut function)
+        .unwrap();
+    assert_eq!(report.result.value, function(&values));
+    assert!(report.accepted_count <= 5);
+    assert!(report.function_calls_count > report.result.function_calls_count);
+}
@@ -0,0 +1,125 @@
+use Newuoa;
+use error::NewuoaError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchReport {
+    pub results: Vec<f64>,
+    pub function_calls_counts: Vec<usize>,
+}
+
+/// Runs the solver over many related problems `function(problem, x)` sharing one total
+/// evaluation budget. Problems are expected in order of relatedness: in the first round
+/// each one starts from the better of its own start point and the solution the previous
+/// problem just reached. The budget is spent in rounds; after the first equal split,
+/// problems that still improved a lot in the previous round receive a larger share.
+pub struct Batch {
+    total_function_calls_count: usize,
+    rounds_count: usize,
+}
+
+impl Default for Batch {
+    fn default() -> Batch {
+        Batch::new()
+    }
+}
+
+impl Batch {
+    pub fn new() -> Batch {
+        Batch {
+            total_function_calls_count: 10000,
+            rounds_count: 3,
+        }
+    }
+
+    pub fn total_function_calls_count(&mut self, value: usize) -> &mut Self {
+        self.total_function_calls_count = value;
+        self
+    }
+
+    pub fn rounds_count(&mut self, value: usize) -> &mut Self {
+        assert!(value >= 1);
+        self.rounds_count = value;
+        self
+    }
+
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [Vec<f64>], function: &mut F)
+            -> Result<BatchReport, NewuoaError>
+            where F: FnMut(usize, &[f64]) -> f64 {
+        let problems_count = values.len();
+        let mut calls = vec![0; problems_count];
+        let mut results = vec![f64::INFINITY; problems_count];
+        if problems_count == 0 {
+            return Ok(BatchReport {results, function_calls_counts: calls});
+        }
+        let n = values[0].len();
+        assert!(values.iter().all(|v| v.len() == n));
+        let npt = 2*n + 1;
+        solver
+            .variables_count(n)
+            .number_of_interpolation_conditions(npt);
+        let mut remaining = self.total_function_calls_count;
+        let mut improvements = vec![1.0; problems_count];
+        for round in 0..self.rounds_count {
+            let round_budget = remaining/(self.rounds_count - round);
+            let total_weight = improvements.iter().sum::<f64>();
+            for problem in 0..problems_count {
+                if round == 0 {
+                    if remaining == 0 {
+                        break;
+                    }
+                    results[problem] = function(problem, &values[problem]);
+                    calls[problem] += 1;
+                    remaining -= 1;
+                    if problem > 0 && remaining > 0 {
+                        let candidate = values[problem - 1].clone();
+                        let value = function(problem, &candidate);
+                        calls[problem] += 1;
+                        remaining -= 1;
+                        if value < results[problem] {
+                            values[problem] = candidate;
+                            results[problem] = value;
+                        }
+                    }
+                }
+                let share = if total_weight > 0.0 {
+                    (round_budget as f64*improvements[problem]/total_weight) as usize
+                } else {
+                    round_budget/problems_count
+                };
+                let share = share.min(remaining);
+                if share < npt + 1 {
+                    improvements[problem] = 0.0;
+                    continue;
+                }
+                let result = {
+                    let mut wrapped = |x: &[f64]| function(problem, x);
+                    solver
+                        .max_function_calls_count(share)
+                        .perform_mut(&mut values[problem], &mut wrapped)?
+                };
+                calls[problem] += result.function_calls_count;
+                remaining -= result.function_calls_count.min(remaining);
+                improvements[problem] =
+                    ((results[problem] - result.value)/(results[problem].abs() + 1.0)).max(0.0);
+                results[problem] = results[problem].min(result.value);
+            }
+            if improvements.iter().all(|v| *v == 0.0) {
+                improvements = vec![1.0; problems_count];
+            }
+        }
+        Ok(BatchReport {results, function_calls_counts: calls})
+    }
+}
+
+#[test]
+fn test_perform_mut_should_warm_start_from_previous_solution() {
+    let mut values = vec![vec![5.0, 5.0]; 2];
+    let report = Batch::new()
+        .total_function_calls_count(2000)
+        .rounds_count(1)
+        .perform_mut(Newuoa::new().final_trust_region_radius(1e-3), &mut values,
+            &mut |problem: usize, x: &[f64]| (x[0] - 0.01*problem as f64).powi(2) + (x[1] - 1.0).powi(2))
+        .unwrap();
+    assert!(report.results.iter().all(|v| *v < 1e-4));
+    assert!(report.function_calls_counts[1] < report.function_calls_counts[0]);
+}
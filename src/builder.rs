@@ -0,0 +1,119 @@
+use std::marker::PhantomData;
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+use types::{Budget, Npt};
+
+/// State of a `Builder` still missing the variables count.
+pub enum NoDimension {}
+
+/// State of a `Builder` ready to run.
+pub enum WithDimension {}
+
+/// Consuming builder of a `Newuoa` that can only configure, build or run once the variables
+/// count is set, instead of falling back to the two variables of `Newuoa::new`.
+pub struct Builder<D> {
+    solver: Newuoa,
+    state: PhantomData<D>,
+}
+
+impl Default for Builder<NoDimension> {
+    fn default() -> Builder<NoDimension> {
+        Builder::new()
+    }
+}
+
+impl Builder<NoDimension> {
+    pub fn new() -> Builder<NoDimension> {
+        Builder {solver: Newuoa::new(), state: PhantomData}
+    }
+
+    /// Also defaults the other settings as `Newuoa::for_dimension`, every other option is
+    /// set afterwards.
+    pub fn variables_count(self, value: usize) -> Builder<WithDimension> {
+        Builder {solver: Newuoa::for_dimension(value), state: PhantomData}
+    }
+}
+
+impl Builder<WithDimension> {
+    pub fn number_of_interpolation_conditions(mut self, value: usize) -> Self {
+        self.solver.number_of_interpolation_conditions(value);
+        self
+    }
+
+    pub fn initial_trust_region_radius(mut self, value: impl Into<f64>) -> Self {
+        self.solver.initial_trust_region_radius(value);
+        self
+    }
+
+    pub fn final_trust_region_radius(mut self, value: impl Into<f64>) -> Self {
+        self.solver.final_trust_region_radius(value);
+        self
+    }
+
+    pub fn max_function_calls_count(mut self, value: usize) -> Self {
+        self.solver.max_function_calls_count(value);
+        self
+    }
+
+    /// Same as `number_of_interpolation_conditions` with an already checked value.
+    pub fn npt(self, value: Npt) -> Self {
+        self.number_of_interpolation_conditions(value.into())
+    }
+
+    /// Same as `max_function_calls_count` with an already checked value.
+    pub fn budget(self, value: Budget) -> Self {
+        self.max_function_calls_count(value.into())
+    }
+
+    /// Applies any other `Newuoa` setter, changing the variables count there defeats the
+    /// purpose of the builder.
+    pub fn configure<F>(mut self, function: F) -> Self where F: FnOnce(&mut Newuoa) {
+        function(&mut self.solver);
+        self
+    }
+
+    pub fn build(self) -> Newuoa {
+        self.solver
+    }
+
+    pub fn perform_mut<F>(mut self, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        self.solver.perform_mut(values, function)
+    }
+}
+
+#[test]
+fn test_builder_should_run_once_dimension_is_set() {
+    let mut values = [1.0, 1.0, 1.0];
+    let result = Builder::new()
+        .variables_count(3)
+        .max_function_calls_count(50)
+        .initial_trust_region_radius(0.5)
+        .perform_mut(&mut values, &mut |x: &[f64]| x.iter().map(|v| v*v).sum())
+        .unwrap();
+    assert_eq!(result.metadata.settings.variables_count, 3);
+    assert_eq!(result.metadata.settings.number_of_interpolation_conditions, 7);
+    assert_eq!(result.function_calls_count, 50);
+}
+
+#[test]
+fn test_builder_should_accept_checked_settings() {
+    use types::Radius;
+
+    let mut values = [1.0, 1.0, 1.0];
+    let result = Builder::new()
+        .variables_count(3)
+        .npt(Npt::new(9).unwrap())
+        .budget(Budget::new(40).unwrap())
+        .initial_trust_region_radius(Radius::new(0.5).unwrap())
+        .final_trust_region_radius(Radius::new(1e-4).unwrap())
+        .perform_mut(&mut values, &mut |x: &[f64]| x.iter().map(|v| v*v).sum())
+        .unwrap();
+    assert_eq!(result.metadata.settings.number_of_interpolation_conditions, 9);
+    assert_eq!(result.metadata.settings.initial_trust_region_radius, 0.5);
+    assert_eq!(result.metadata.settings.final_trust_region_radius, 1e-4);
+    assert_eq!(result.function_calls_count, 40);
+}
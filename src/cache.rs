@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStatistics {
+    pub hits_count: usize,
+    pub misses_count: usize,
+}
+
+/// Objective wrapper memoizing values, so restarts and outer loops revisiting points of
+/// an expensive objective do not evaluate it again. With a positive tolerance a point
+/// within that max-norm distance of a cached one reuses its value, the points being
+/// bucketed on a grid of twice the tolerance so only the cells overlapping that distance
+/// are searched. Like `Instrumented`,
+/// pass `as_fn_mut()` where an `FnMut(&[f64]) -> f64` is expected, and keep one `Cached`
+/// across runs only while the objective stays the same.
+pub struct Cached<F> {
+    function: F,
+    tolerance: f64,
+    exact: HashMap<Vec<u64>, f64>,
+    cells: HashMap<Vec<i64>, Vec<(Vec<f64>, f64)>>,
+    statistics: CacheStatistics,
+}
+
+impl<F> Cached<F> where F: FnMut(&[f64]) -> f64 {
+    pub fn new(function: F) -> Cached<F> {
+        Cached {
+            function,
+            tolerance: 0.0,
+            exact: HashMap::new(),
+            cells: HashMap::new(),
+            statistics: CacheStatistics::default(),
+        }
+    }
+
+    pub fn tolerance(mut self, value: f64) -> Self {
+        assert!(value >= 0.0);
+        self.tolerance = value;
+        self
+    }
+
+    pub fn call(&mut self, x: &[f64]) -> f64 {
+        let key = x.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        let cached = match self.exact.get(&key) {
+            Some(v) => Some(*v),
+            None if self.tolerance > 0.0 => self.nearby(x),
+            None => None,
+        };
+        if let Some(value) = cached {
+            self.statistics.hits_count += 1;
+            return value;
+        }
+        self.statistics.misses_count += 1;
+        let value = (self.function)(x);
+        self.exact.insert(key, value);
+        if self.tolerance > 0.0 {
+            let cell = x.iter().map(|v| self.cell(*v)).collect();
+            self.cells.entry(cell).or_default().push((x.to_vec(), value));
+        }
+        value
+    }
+
+    /// Runs `solver` on the cached objective and reports the hits of this run in
+    /// `cache_hits`, `function_calls_count` still counting every call the solver made.
+    pub fn perform_mut(&mut self, solver: &mut Newuoa, values: &mut [f64])
+            -> Result<OptimizationResult, NewuoaError> {
+        let hits_count = self.statistics.hits_count;
+        let mut result = solver.perform_mut(values, &mut self.as_fn_mut())?;
+        result.cache_hits = self.statistics.hits_count - hits_count;
+        Ok(result)
+    }
+
+    pub fn as_fn_mut<'a>(&'a mut self) -> impl FnMut(&[f64]) -> f64 + 'a where F: 'a {
+        move |x: &[f64]| self.call(x)
+    }
+
+    pub fn statistics(&self) -> CacheStatistics {
+        self.statistics
+    }
+
+    pub fn len(&self) -> usize {
+        self.exact.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.exact.clear();
+        self.cells.clear();
+        self.statistics = CacheStatistics::default();
+    }
+
+    pub fn into_inner(self) -> F {
+        self.function
+    }
+
+    fn cell(&self, value: f64) -> i64 {
+        (value/(2.0*self.tolerance)).floor() as i64
+    }
+
+    /// Each coordinate within the tolerance of `x` lies in one of two cells, so there are at
+    /// most `2^n` cells to look up. When they outnumber the nonempty cells those are
+    /// scanned instead.
+    fn nearby(&self, x: &[f64]) -> Option<f64> {
+        let bounds = x.iter()
+            .map(|v| (self.cell(v - self.tolerance), self.cell(v + self.tolerance)))
+            .collect::<Vec<_>>();
+        let split = bounds.iter().filter(|v| v.0 != v.1).count();
+        let close = |p: &&(Vec<f64>, f64)| {
+            p.0.iter().zip(x.iter()).all(|(a, b)| (a - b).abs() <= self.tolerance)
+        };
+        if split >= 64 || (1usize << split) > self.cells.len() {
+            return self.cells.values().flat_map(|v| v.iter()).find(close).map(|p| p.1);
+        }
+        let mut cell = bounds.iter().map(|v| v.0).collect::<Vec<_>>();
+        for mask in 0..(1usize << split) {
+            let mut bit = 0;
+            for (i, &(lower, upper)) in bounds.iter().enumerate() {
+                if lower != upper {
+                    cell[i] = if mask & (1 << bit) == 0 { lower } else { upper };
+                    bit += 1;
+                }
+            }
+            if let Some(p) = self.cells.get(&cell).and_then(|v| v.iter().find(close)) {
+                return Some(p.1);
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn test_cached_should_reuse_values_within_tolerance() {
+    let mut calls_count = 0;
+    {
+        let mut cached = Cached::new(|x: &[f64]| {
+            calls_count += 1;
+            x[0] + x[1]
+        }).tolerance(1e-3);
+        assert_eq!(cached.call(&[1.0, 2.0]), 3.0);
+        assert_eq!(cached.call(&[1.0, 2.0]), 3.0);
+        assert_eq!(cached.call(&[1.0005, 2.0]), 3.0);
+        assert_eq!(cached.call(&[1.5, 2.0]), 3.5);
+        assert_eq!(cached.statistics(), CacheStatistics {hits_count: 2, misses_count: 2});
+    }
+    assert_eq!(calls_count, 2);
+}
+
+#[test]
+fn test_cached_should_find_points_in_neighbour_cells() {
+    let mut cached = Cached::new(|x: &[f64]| x[0] - x[1]).tolerance(0.5);
+    for i in 0..10 {
+        cached.call(&[i as f64*10.0, 0.0]);
+    }
+    assert_eq!(cached.call(&[20.4, -0.4]), 20.0);
+    assert_eq!(cached.call(&[19.6, 0.45]), 20.0);
+    assert_eq!(cached.call(&[25.0, 0.0]), 25.0);
+    assert_eq!(cached.statistics(), CacheStatistics {hits_count: 2, misses_count: 11});
+}
+
+#[test]
+fn test_perform_mut_should_report_cache_hits() {
+    let mut cached = Cached::new(|x: &[f64]| x[0]*x[0] + x[1]*x[1]);
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(30);
+    let first = cached.perform_mut(&mut solver, &mut [1.0, 1.0]).unwrap();
+    assert_eq!(first.cache_hits, cached.statistics().hits_count);
+    let second = cached.perform_mut(&mut solver, &mut [1.0, 1.0]).unwrap();
+    assert_eq!(second.cache_hits, second.function_calls_count);
+    assert_eq!(second.solution, first.solution);
+}
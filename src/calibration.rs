@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+
+use Newuoa;
+use error::NewuoaError;
+
+/// Kind and typical magnitude of a calibration parameter. The solver works on parameters
+/// divided by their scale, so a scale of `1e-3` for a length in meters and `1e-2` for an
+/// angle in radians makes a unit step comparable across both.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variable {
+    Linear(f64),
+    /// Periodic variable in radians, wrapped into `(-pi, pi]`.
+    Angle(f64),
+}
+
+impl Variable {
+    fn scale(&self) -> f64 {
+        match *self {
+            Variable::Linear(scale) | Variable::Angle(scale) => scale,
+        }
+    }
+
+    fn normalize(&self, value: f64) -> f64 {
+        match *self {
+            Variable::Linear(_) => value,
+            Variable::Angle(_) => wrap_angle(value),
+        }
+    }
+}
+
+pub fn wrap_angle(value: f64) -> f64 {
+    let result = (value + PI).rem_euclid(2.0*PI) - PI;
+    if result == -PI { PI } else { result }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalibrationReport {
+    pub sum_of_squares: f64,
+    /// Euclidean norm of the residual vector of each measurement.
+    pub residual_norms: Vec<f64>,
+}
+
+/// Least-squares calibration over pose measurements: `residuals(parameters, measurement)`
+/// returns the residual vector of a single measurement.
+pub struct Calibration {
+    variables: Vec<Variable>,
+}
+
+impl Default for Calibration {
+    fn default() -> Calibration {
+        Calibration::new()
+    }
+}
+
+impl Calibration {
+    pub fn new() -> Calibration {
+        Calibration {variables: Vec::new()}
+    }
+
+    pub fn variables(&mut self, value: &[Variable]) -> &mut Self {
+        assert!(value.iter().all(|v| v.scale() > 0.0));
+        self.variables = value.to_vec();
+        self
+    }
+
+    pub fn calibrate<M, R>(&self, solver: &mut Newuoa, parameters: &mut [f64], measurements: &[M],
+            residuals: &R) -> Result<CalibrationReport, NewuoaError>
+            where R: Fn(&[f64], &M) -> Vec<f64> {
+        let n = parameters.len();
+        assert!(self.variables.is_empty() || self.variables.len() == n);
+        let mut scaled = (0..n).map(|i| parameters[i]/self.variable(i).scale())
+            .collect::<Vec<_>>();
+        {
+            let function = |x: &[f64]| -> f64 {
+                let parameters = self.to_parameters(x);
+                measurements.iter()
+                    .flat_map(|m| residuals(&parameters, m))
+                    .map(|r| r*r)
+                    .sum()
+            };
+            solver
+                .variables_count(n)
+                .number_of_interpolation_conditions(2*n + 1)
+                .perform(&mut scaled, &function)?;
+        }
+        parameters.copy_from_slice(&self.to_parameters(&scaled));
+        let residual_norms = measurements.iter()
+            .map(|m| residuals(parameters, m).iter().map(|r| r*r).sum::<f64>().sqrt())
+            .collect::<Vec<_>>();
+        Ok(CalibrationReport {
+            sum_of_squares: residual_norms.iter().map(|v| v*v).sum(),
+            residual_norms,
+        })
+    }
+
+    fn variable(&self, index: usize) -> Variable {
+        self.variables.get(index).cloned().unwrap_or(Variable::Linear(1.0))
+    }
+
+    fn to_parameters(&self, x: &[f64]) -> Vec<f64> {
+        x.iter().enumerate()
+            .map(|(i, v)| {
+                let variable = self.variable(i);
+                variable.normalize(v*variable.scale())
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_wrap_angle_should_map_into_half_open_interval() {
+    assert_eq!(wrap_angle(PI), PI);
+    assert_eq!(wrap_angle(-PI), PI);
+    assert!((wrap_angle(3.0*PI/2.0) + PI/2.0).abs() < 1e-12);
+    assert!((wrap_angle(-5.0*PI/2.0) + PI/2.0).abs() < 1e-12);
+}
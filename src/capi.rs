@@ -0,0 +1,123 @@
+//! C API declared in `include/newuoa_rs.h`. The crate builds as an rlib only, a shared or
+//! static library for C callers comes from
+//! `cargo rustc --release --lib --features capi --crate-type cdylib` or `staticlib`.
+
+use std::os::raw::{c_int, c_void};
+use std::slice::from_raw_parts_mut;
+
+use error::NewuoaError;
+use minimize::Options;
+use result::TerminationReason;
+
+/// Objective of `newuoa_rs_minimize`, called with the user data and the point.
+pub type Function = extern "C" fn(data: *mut c_void, n: usize, x: *const f64) -> f64;
+
+/// Zero or negative fields take the `minimize::Options` defaults.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct NewuoaRsOptions {
+    pub number_of_interpolation_conditions: usize,
+    pub initial_trust_region_radius: f64,
+    pub final_trust_region_radius: f64,
+    pub max_function_calls_count: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct NewuoaRsResult {
+    pub value: f64,
+    pub function_calls_count: usize,
+    pub final_trust_region_radius: f64,
+    /// `TerminationReason` in declaration order starting from zero.
+    pub termination_reason: c_int,
+}
+
+pub const NEWUOA_RS_OK: c_int = 0;
+pub const NEWUOA_RS_NULL_POINTER: c_int = -1;
+pub const NEWUOA_RS_DIMENSION_TOO_SMALL: c_int = -2;
+pub const NEWUOA_RS_INVALID_INTERPOLATION_CONDITIONS: c_int = -3;
+pub const NEWUOA_RS_RADIUS_ORDER_INVALID: c_int = -4;
+pub const NEWUOA_RS_INVALID_ARGUMENT: c_int = -5;
+
+/// Minimizes `function` from the `n` values of `x`, which receives the best point found.
+/// `options` may be null, `result` may be null when only the point is needed. Returns
+/// `NEWUOA_RS_OK` or one of the negative error codes declared in `include/newuoa_rs.h`.
+///
+/// # Safety
+///
+/// `x` must point to `n` writable values, `options` and `result` must be null or valid, and
+/// `function` must not unwind.
+#[no_mangle]
+pub unsafe extern "C" fn newuoa_rs_minimize(function: Function, data: *mut c_void, n: usize,
+        x: *mut f64, options: *const NewuoaRsOptions, result: *mut NewuoaRsResult) -> c_int {
+    if x.is_null() {
+        return NEWUOA_RS_NULL_POINTER;
+    }
+    let values = from_raw_parts_mut(x, n);
+    let options = options.as_ref().map_or(Options::default(), |v| Options {
+        number_of_interpolation_conditions: Some(v.number_of_interpolation_conditions)
+            .filter(|v| *v > 0),
+        initial_trust_region_radius: Some(v.initial_trust_region_radius).filter(|v| *v > 0.0),
+        final_trust_region_radius: Some(v.final_trust_region_radius).filter(|v| *v > 0.0),
+        max_function_calls_count: Some(v.max_function_calls_count).filter(|v| *v > 0),
+    });
+    let solution = options.solver(values).and_then(|mut v| {
+        v.perform_mut(values, &mut |point: &[f64]| function(data, point.len(), point.as_ptr()))
+    });
+    match solution {
+        Ok(v) => {
+            if let Some(result) = result.as_mut() {
+                *result = NewuoaRsResult {
+                    value: v.value,
+                    function_calls_count: v.function_calls_count,
+                    final_trust_region_radius: v.final_trust_region_radius,
+                    termination_reason: termination_reason_code(v.termination_reason),
+                };
+            }
+            NEWUOA_RS_OK
+        }
+        Err(e) => error_code(&e),
+    }
+}
+
+fn termination_reason_code(value: TerminationReason) -> c_int {
+    match value {
+        TerminationReason::Converged => 0,
+        TerminationReason::MaxFunctionCallsReached => 1,
+        TerminationReason::StoppedByUser => 2,
+        TerminationReason::TimeLimit => 3,
+        TerminationReason::TargetReached => 4,
+        TerminationReason::FunctionToleranceReached => 5,
+        TerminationReason::NonFiniteObjective => 6,
+        TerminationReason::ObjectiveFailed => 7,
+    }
+}
+
+fn error_code(error: &NewuoaError) -> c_int {
+    match *error {
+        NewuoaError::DimensionTooSmall {..} => NEWUOA_RS_DIMENSION_TOO_SMALL,
+        NewuoaError::InvalidInterpolationConditions {..} => NEWUOA_RS_INVALID_INTERPOLATION_CONDITIONS,
+        NewuoaError::RadiusOrderInvalid {..} => NEWUOA_RS_RADIUS_ORDER_INVALID,
+        _ => NEWUOA_RS_INVALID_ARGUMENT,
+    }
+}
+
+#[test]
+fn test_newuoa_rs_minimize_should_write_solution_back() {
+    extern "C" fn sphere(_: *mut c_void, n: usize, x: *const f64) -> f64 {
+        unsafe { ::std::slice::from_raw_parts(x, n) }.iter().map(|v| v*v).sum()
+    }
+    let mut values = [1.0, 1.0];
+    let options = NewuoaRsOptions {max_function_calls_count: 20, ..NewuoaRsOptions::default()};
+    let mut result = NewuoaRsResult::default();
+    let code = unsafe {
+        newuoa_rs_minimize(sphere, ::std::ptr::null_mut(), 2, values.as_mut_ptr(), &options, &mut result)
+    };
+    assert_eq!(code, NEWUOA_RS_OK);
+    assert_eq!(result.function_calls_count, 20);
+    assert_eq!(result.value, values[0]*values[0] + values[1]*values[1]);
+    let code = unsafe {
+        newuoa_rs_minimize(sphere, ::std::ptr::null_mut(), 1, values.as_mut_ptr(), &options, &mut result)
+    };
+    assert_eq!(code, NEWUOA_RS_DIMENSION_TOO_SMALL);
+}
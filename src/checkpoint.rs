@@ -0,0 +1,462 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use Newuoa;
+use compression;
+use configuration::Configuration;
+use iterates::Iterate;
+use metadata::Settings;
+use prior::{Distribution, Prior};
+use types::{NonFinitePolicy, Verbosity};
+
+pub const MAGIC: [u8; 8] = *b"NEWUOACK";
+pub const VERSION: u32 = 2;
+
+/// Incumbent of a run together with the configuration it was started with.
+///
+/// The binary format is independent of the platform: an 8 byte magic, the format version
+/// and the payload length, the payload, and a CRC-32 of everything after the magic. All
+/// integers are little-endian `u32`/`u64` and floats are stored as their IEEE 754 bits.
+/// Version 1 payloads, which only hold the settings, are still read with the defaults of
+/// every other option.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub configuration: Configuration,
+    pub point: Vec<f64>,
+    pub value: f64,
+    pub function_calls_count: usize,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    ChecksumMismatch {expected: u32, actual: u32},
+    Truncated,
+    /// A field holds a value no checkpoint is written with.
+    Malformed,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CheckpointError::Io(ref error) => write!(formatter, "checkpoint io error: {}", error),
+            CheckpointError::BadMagic => write!(formatter, "not a newuoa checkpoint"),
+            CheckpointError::UnsupportedVersion(version) =>
+                write!(formatter, "unsupported checkpoint version {}, expected {}", version, VERSION),
+            CheckpointError::ChecksumMismatch {expected, actual} =>
+                write!(formatter, "checkpoint checksum mismatch: expected {:08x}, got {:08x}",
+                    expected, actual),
+            CheckpointError::Truncated => write!(formatter, "checkpoint is truncated"),
+            CheckpointError::Malformed => write!(formatter, "checkpoint is malformed"),
+        }
+    }
+}
+
+impl Error for CheckpointError {}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> CheckpointError {
+        CheckpointError::Io(error)
+    }
+}
+
+impl Checkpoint {
+    pub fn from_iterate(configuration: Configuration, iterate: &Iterate) -> Checkpoint {
+        Checkpoint {
+            configuration,
+            point: iterate.point.clone(),
+            value: iterate.value,
+            function_calls_count: iterate.function_calls_count,
+        }
+    }
+
+    /// Solver with the checkpoint configuration, to be run from `point`.
+    pub fn solver(&self) -> Newuoa {
+        Newuoa::from_configuration(&self.configuration)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let configuration = &self.configuration;
+        let settings = &configuration.settings;
+        let mut payload = Vec::new();
+        put_u64(&mut payload, settings.variables_count as u64);
+        put_u64(&mut payload, settings.number_of_interpolation_conditions as u64);
+        put_f64(&mut payload, settings.initial_trust_region_radius);
+        put_f64(&mut payload, settings.final_trust_region_radius);
+        put_u64(&mut payload, settings.max_function_calls_count as u64);
+        put_u64(&mut payload, self.function_calls_count as u64);
+        put_f64(&mut payload, self.value);
+        put_f64s(&mut payload, &self.point);
+        put_u8(&mut payload, configuration.max_duration.is_some() as u8);
+        if let Some(duration) = configuration.max_duration {
+            put_u64(&mut payload, duration.as_secs());
+            put_u32(&mut payload, duration.subsec_nanos());
+        }
+        for value in [configuration.stop_at_objective, configuration.ftol_rel, configuration.ftol_abs].iter() {
+            put_u8(&mut payload, value.is_some() as u8);
+            if let Some(value) = *value {
+                put_f64(&mut payload, value);
+            }
+        }
+        match configuration.non_finite_policy {
+            NonFinitePolicy::WorstValue => put_u8(&mut payload, 0),
+            NonFinitePolicy::Penalty(penalty) => {
+                put_u8(&mut payload, 1);
+                put_f64(&mut payload, penalty);
+            }
+            NonFinitePolicy::Abort => put_u8(&mut payload, 2),
+        }
+        put_u8(&mut payload, configuration.record_history as u8);
+        put_u8(&mut payload, configuration.verbosity as u8);
+        put_u8(&mut payload, configuration.maximize as u8);
+        put_u8(&mut payload, configuration.scaling.is_some() as u8);
+        if let Some(ref scaling) = configuration.scaling {
+            put_f64s(&mut payload, scaling);
+        }
+        put_u64(&mut payload, configuration.fixed_variables.len() as u64);
+        for &(index, value) in configuration.fixed_variables.iter() {
+            put_u64(&mut payload, index as u64);
+            put_f64(&mut payload, value);
+        }
+        put_u8(&mut payload, configuration.checkpoint.is_some() as u8);
+        if let Some((function_calls_count, ref path)) = configuration.checkpoint {
+            let path = path.to_string_lossy();
+            put_u64(&mut payload, function_calls_count as u64);
+            put_u64(&mut payload, path.len() as u64);
+            payload.extend_from_slice(path.as_bytes());
+        }
+        put_u8(&mut payload, configuration.prior.is_some() as u8);
+        if let Some(ref prior) = configuration.prior {
+            put_u8(&mut payload, prior.distribution() as u8);
+            put_f64s(&mut payload, prior.means());
+            put_f64s(&mut payload, prior.strengths());
+        }
+        let mut body = Vec::with_capacity(payload.len() + 12);
+        put_u32(&mut body, VERSION);
+        put_u64(&mut body, payload.len() as u64);
+        body.extend_from_slice(&payload);
+        let mut result = MAGIC.to_vec();
+        result.extend_from_slice(&body);
+        put_u32(&mut result, crc32(&body));
+        result
+    }
+
+    #[cfg(feature = "zstd")]
+    pub fn to_compressed_bytes(&self, level: i32) -> io::Result<Vec<u8>> {
+        compression::compress(&self.to_bytes(), level)
+    }
+
+    /// Accepts both plain and zstd compressed checkpoints.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Checkpoint, CheckpointError> {
+        let bytes = compression::decompress(bytes)?;
+        Checkpoint::from_plain_bytes(&bytes)
+    }
+
+    fn from_plain_bytes(bytes: &[u8]) -> Result<Checkpoint, CheckpointError> {
+        if bytes.len() < MAGIC.len() || bytes[..MAGIC.len()] != MAGIC {
+            return Err(CheckpointError::BadMagic);
+        }
+        let mut reader = Reader {bytes: &bytes[MAGIC.len()..]};
+        let version = reader.u32()?;
+        if version != 1 && version != VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+        let length = reader.u64()? as usize;
+        if length.checked_add(4).is_none_or(|v| reader.bytes.len() < v) {
+            return Err(CheckpointError::Truncated);
+        }
+        let body = &bytes[MAGIC.len()..MAGIC.len() + 12 + length];
+        let expected = Reader {bytes: &reader.bytes[length..]}.u32()?;
+        let actual = crc32(body);
+        if expected != actual {
+            return Err(CheckpointError::ChecksumMismatch {expected, actual});
+        }
+        let mut reader = Reader {bytes: &reader.bytes[..length]};
+        let settings = Settings {
+            variables_count: reader.u64()? as usize,
+            number_of_interpolation_conditions: reader.u64()? as usize,
+            initial_trust_region_radius: reader.f64()?,
+            final_trust_region_radius: reader.f64()?,
+            max_function_calls_count: reader.u64()? as usize,
+        };
+        let function_calls_count = reader.u64()? as usize;
+        let value = reader.f64()?;
+        let point = reader.f64s()?;
+        let mut configuration = Newuoa::from_settings(&settings).configuration();
+        if version >= 2 {
+            read_options(&mut reader, &mut configuration)?;
+        }
+        Ok(Checkpoint {configuration, point, value, function_calls_count})
+    }
+
+    pub fn write<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
+        writer.write_all(&self.to_bytes())
+    }
+
+    pub fn read<R>(reader: &mut R) -> Result<Checkpoint, CheckpointError> where R: Read {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Checkpoint::from_bytes(&bytes)
+    }
+
+    /// Compressed when `path` ends with `.zst`, see `compression::for_path`.
+    pub fn save<P>(&self, path: P) -> io::Result<()> where P: AsRef<Path> {
+        let path = path.as_ref();
+        File::create(path)?.write_all(&compression::for_path(path, &self.to_bytes())?)
+    }
+
+    /// Saves through a temporary file renamed over `path`, so that a crash while writing
+    /// leaves the previous checkpoint intact. Compressed like `save`.
+    pub fn replace<P>(&self, path: P) -> io::Result<()> where P: AsRef<Path> {
+        let path = path.as_ref();
+        let bytes = self.to_bytes();
+        let bytes = compression::for_path(path, &bytes)?;
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        File::create(&temporary)?.write_all(&bytes)?;
+        fs::rename(&temporary, path)
+    }
+
+    #[cfg(feature = "zstd")]
+    pub fn save_compressed<P>(&self, path: P, level: i32) -> io::Result<()> where P: AsRef<Path> {
+        File::create(path)?.write_all(&self.to_compressed_bytes(level)?)
+    }
+
+    pub fn load<P>(path: P) -> Result<Checkpoint, CheckpointError> where P: AsRef<Path> {
+        Checkpoint::read(&mut File::open(path)?)
+    }
+}
+
+fn read_options(reader: &mut Reader, configuration: &mut Configuration) -> Result<(), CheckpointError> {
+    if reader.flag()? {
+        let seconds = reader.u64()?;
+        let nanos = reader.u32()?;
+        if nanos >= 1_000_000_000 {
+            return Err(CheckpointError::Malformed);
+        }
+        configuration.max_duration = Some(Duration::new(seconds, nanos));
+    }
+    for value in [&mut configuration.stop_at_objective, &mut configuration.ftol_rel,
+            &mut configuration.ftol_abs] {
+        *value = if reader.flag()? { Some(reader.f64()?) } else { None };
+    }
+    configuration.non_finite_policy = match reader.u8()? {
+        0 => NonFinitePolicy::WorstValue,
+        1 => NonFinitePolicy::Penalty(reader.f64()?),
+        2 => NonFinitePolicy::Abort,
+        _ => return Err(CheckpointError::Malformed),
+    };
+    configuration.record_history = reader.flag()?;
+    configuration.verbosity = match reader.u8()? {
+        0 => Verbosity::Silent,
+        1 => Verbosity::Summary,
+        2 => Verbosity::PerRho,
+        3 => Verbosity::PerEval,
+        _ => return Err(CheckpointError::Malformed),
+    };
+    configuration.maximize = reader.flag()?;
+    configuration.scaling = if reader.flag()? { Some(reader.f64s()?) } else { None };
+    let fixed_count = reader.u64()? as usize;
+    configuration.fixed_variables = Vec::new();
+    for _ in 0..fixed_count {
+        configuration.fixed_variables.push((reader.u64()? as usize, reader.f64()?));
+    }
+    if reader.flag()? {
+        let function_calls_count = reader.u64()? as usize;
+        let path_len = reader.u64()? as usize;
+        let path = String::from_utf8(reader.take(path_len)?.to_vec()).map_err(|_| CheckpointError::Malformed)?;
+        configuration.checkpoint = Some((function_calls_count, PathBuf::from(path)));
+    }
+    if reader.flag()? {
+        let distribution = match reader.u8()? {
+            0 => Distribution::Gaussian,
+            1 => Distribution::Laplace,
+            _ => return Err(CheckpointError::Malformed),
+        };
+        let means = reader.f64s()?;
+        let strengths = reader.f64s()?;
+        if means.len() != strengths.len() || !strengths.iter().all(|v| *v >= 0.0) {
+            return Err(CheckpointError::Malformed);
+        }
+        configuration.prior = Some(Prior::new(distribution, &means, &strengths));
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], CheckpointError> {
+        if self.bytes.len() < count {
+            return Err(CheckpointError::Truncated);
+        }
+        let (result, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(result)
+    }
+
+    fn u8(&mut self) -> Result<u8, CheckpointError> {
+        self.take(1).map(|v| v[0])
+    }
+
+    fn flag(&mut self) -> Result<bool, CheckpointError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(CheckpointError::Malformed),
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32, CheckpointError> {
+        let mut buffer = [0; 4];
+        buffer.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buffer))
+    }
+
+    fn u64(&mut self) -> Result<u64, CheckpointError> {
+        let mut buffer = [0; 8];
+        buffer.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buffer))
+    }
+
+    fn f64(&mut self) -> Result<f64, CheckpointError> {
+        self.u64().map(f64::from_bits)
+    }
+
+    /// A `u64` length followed by that many floats.
+    fn f64s(&mut self) -> Result<Vec<f64>, CheckpointError> {
+        let len = self.u64()? as usize;
+        if len.checked_mul(8).is_none_or(|v| self.bytes.len() < v) {
+            return Err(CheckpointError::Truncated);
+        }
+        (0..len).map(|_| self.f64()).collect()
+    }
+}
+
+fn put_u8(buffer: &mut Vec<u8>, value: u8) {
+    buffer.push(value);
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_f64(buffer: &mut Vec<u8>, value: f64) {
+    put_u64(buffer, value.to_bits());
+}
+
+fn put_f64s(buffer: &mut Vec<u8>, values: &[f64]) {
+    put_u64(buffer, values.len() as u64);
+    for v in values.iter() {
+        put_f64(buffer, *v);
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes.iter() {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+fn sample() -> Checkpoint {
+    let mut solver = Newuoa::new();
+    solver
+        .variables_count(3)
+        .max_duration(Duration::new(3, 5))
+        .ftol_rel(1e-3)
+        .non_finite_policy(NonFinitePolicy::Penalty(1e3))
+        .verbosity(Verbosity::PerRho)
+        .maximize(true)
+        .scaling(&[1.0, 2.0, 3.0])
+        .fix_variable(1, 0.5)
+        .checkpoint_every(10, "checkpoint.bin")
+        .prior(Prior::laplace(&[0.0, 1.0, 2.0], &[1.0, 1.0, 0.5]));
+    Checkpoint {
+        configuration: solver.configuration(),
+        point: vec![1.5, 0.5, -2.25],
+        value: 0.125,
+        function_calls_count: 42,
+    }
+}
+
+#[test]
+fn test_from_bytes_should_restore_checkpoint() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    let checkpoint = sample();
+    assert_eq!(Checkpoint::from_bytes(&checkpoint.to_bytes()).unwrap(), checkpoint);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_from_bytes_should_restore_compressed_checkpoint() {
+    let checkpoint = sample();
+    let bytes = checkpoint.to_compressed_bytes(3).unwrap();
+    assert_eq!(Checkpoint::from_bytes(&bytes).unwrap(), checkpoint);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_checkpoint_every_should_compress_zst_paths() {
+    let path = ::std::env::temp_dir().join(format!("newuoa-checkpoint-{}.zst", ::std::process::id()));
+    let result = Newuoa::new()
+        .max_function_calls_count(20)
+        .checkpoint_every(5, &path)
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    let bytes = fs::read(&path);
+    let checkpoint = Checkpoint::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(result.checkpoint_error, None);
+    assert!(compression::is_compressed(&bytes.unwrap()));
+    assert_eq!(checkpoint.unwrap().function_calls_count, 20);
+}
+
+#[test]
+fn test_from_bytes_should_reject_corrupted_or_other_version() {
+    let mut bytes = sample().to_bytes();
+    let last = bytes.len() - 5;
+    bytes[last] ^= 1;
+    match Checkpoint::from_bytes(&bytes) {
+        Err(CheckpointError::ChecksumMismatch {..}) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+    bytes[MAGIC.len()] = 3;
+    match Checkpoint::from_bytes(&bytes) {
+        Err(CheckpointError::UnsupportedVersion(3)) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_from_bytes_should_reject_overflowing_lengths() {
+    let mut bytes = MAGIC.to_vec();
+    put_u32(&mut bytes, VERSION);
+    put_u64(&mut bytes, u64::MAX);
+    match Checkpoint::from_bytes(&bytes) {
+        Err(CheckpointError::Truncated) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+    let mut reader = Reader {bytes: &u64::MAX.to_le_bytes()};
+    match reader.f64s() {
+        Err(CheckpointError::Truncated) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,41 @@
+use penalty::Constraint;
+
+/// Adapters building new objectives from closures, in the manner of iterator adapters:
+/// `objective.scaled(2.0).shifted(1.0)` evaluates to `2 f(x) + 1`.
+pub trait Combinators: FnMut(&[f64]) -> f64 + Sized {
+    fn scaled(mut self, factor: f64) -> impl FnMut(&[f64]) -> f64 {
+        move |x: &[f64]| factor*self(x)
+    }
+
+    fn shifted(mut self, offset: f64) -> impl FnMut(&[f64]) -> f64 {
+        move |x: &[f64]| self(x) + offset
+    }
+
+    /// Caps values at `max`, NaN included, e.g. to keep failed simulations from dominating
+    /// the model.
+    fn clamped(mut self, max: f64) -> impl FnMut(&[f64]) -> f64 {
+        move |x: &[f64]| {
+            let value = self(x);
+            if value > max || value.is_nan() { max } else { value }
+        }
+    }
+
+    /// Adds `weight` times the squared violation of `constraint`, see `penalty::penalized`.
+    fn with_penalty(mut self, constraint: Constraint, weight: f64) -> impl FnMut(&[f64]) -> f64 {
+        move |x: &[f64]| self(x) + weight*constraint.violation(x).powi(2)
+    }
+}
+
+impl<F> Combinators for F where F: FnMut(&[f64]) -> f64 {}
+
+#[test]
+fn test_combinators_should_compose() {
+    let mut objective = (|x: &[f64]| x[0])
+        .scaled(2.0)
+        .shifted(1.0)
+        .with_penalty(Constraint::inequality(|x: &[f64]| x[0] - 1.0), 10.0)
+        .clamped(100.0);
+    assert_eq!(objective(&[0.5]), 2.0);
+    assert_eq!(objective(&[2.0]), 15.0);
+    assert_eq!(objective(&[10.0]), 100.0);
+}
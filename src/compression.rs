@@ -0,0 +1,76 @@
+use std::borrow::Cow;
+use std::io;
+use std::path::Path;
+
+/// Frame magic number every zstd stream starts with.
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Level of the files compressed because of their `.zst` extension.
+pub const DEFAULT_LEVEL: i32 = 3;
+
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ZSTD_MAGIC)
+}
+
+pub fn is_compressed_path(path: &Path) -> bool {
+    path.extension().is_some_and(|v| v == "zst")
+}
+
+#[cfg(feature = "zstd")]
+pub fn compress(bytes: &[u8], level: i32) -> io::Result<Vec<u8>> {
+    ::zstd::encode_all(bytes, level)
+}
+
+/// Contents of a file written to `path`: `bytes` compressed at `DEFAULT_LEVEL` when the
+/// path ends with `.zst`, which fails when the `zstd` feature is disabled, else as is.
+pub fn for_path<'a>(path: &Path, bytes: &'a [u8]) -> io::Result<Cow<'a, [u8]>> {
+    if !is_compressed_path(path) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    encode(bytes).map(Cow::Owned)
+}
+
+#[cfg(feature = "zstd")]
+fn encode(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    compress(bytes, DEFAULT_LEVEL)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn encode(_: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "zstd compression requires the zstd feature"))
+}
+
+/// Returns the input as is unless it is a zstd stream, which is decompressed. Fails on
+/// compressed input when the `zstd` feature is disabled.
+pub fn decompress(bytes: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    if !is_compressed(bytes) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    decode(bytes).map(Cow::Owned)
+}
+
+#[cfg(feature = "zstd")]
+fn decode(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    ::zstd::decode_all(bytes)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decode(_: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, "zstd compressed data requires the zstd feature"))
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_decompress_should_restore_compressed_data() {
+    let data = vec![7u8; 1000];
+    let compressed = compress(&data, 3).unwrap();
+    assert!(is_compressed(&compressed));
+    assert_eq!(&*decompress(&compressed).unwrap(), &data[..]);
+}
+
+#[test]
+fn test_for_path_should_keep_plain_files_as_is() {
+    let data = [1u8, 2, 3];
+    assert_eq!(&*for_path(Path::new("history.csv"), &data).unwrap(), &data[..]);
+    assert_eq!(for_path(Path::new("history.csv.zst"), &data).is_ok(), cfg!(feature = "zstd"));
+}
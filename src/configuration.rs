@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use Newuoa;
+use error::NewuoaError;
+use metadata::Settings;
+use prior::Prior;
+use result::OptimizationResult;
+use types::{NonFinitePolicy, Verbosity};
+
+/// Every option of a `Newuoa` except the observer, see `Newuoa::configuration`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Configuration {
+    pub settings: Settings,
+    pub max_duration: Option<Duration>,
+    pub stop_at_objective: Option<f64>,
+    pub ftol_rel: Option<f64>,
+    pub ftol_abs: Option<f64>,
+    pub non_finite_policy: NonFinitePolicy,
+    pub record_history: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub verbosity: Verbosity,
+    pub maximize: bool,
+    pub scaling: Option<Vec<f64>>,
+    pub fixed_variables: Vec<(usize, f64)>,
+    pub checkpoint: Option<(usize, PathBuf)>,
+    pub prior: Option<Prior>,
+}
+
+impl Configuration {
+    /// Runs a solver with this configuration through a shared reference, so one
+    /// configuration can launch runs from many threads. Each run allocates its own
+    /// working space, keep a `Newuoa` per thread to reuse it.
+    pub fn perform_mut<F>(&self, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        Newuoa::from_configuration(self).perform_mut(values, function)
+    }
+}
+
+#[test]
+fn test_perform_mut_should_run_concurrently_from_shared_configuration() {
+    use std::thread;
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(30);
+    let configuration = solver.configuration();
+    let results = thread::scope(|scope| {
+        let handles = (0..4)
+            .map(|i| {
+                let configuration = &configuration;
+                scope.spawn(move || {
+                    let mut values = [1.0 + i as f64, 1.0];
+                    configuration.perform_mut(&mut values, &mut |x: &[f64]| x[0]*x[0] + x[1]*x[1])
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|v| v.join().unwrap().unwrap()).collect::<Vec<_>>()
+    });
+    assert!(results.iter().all(|v| v.function_calls_count == 30));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_configuration_should_round_trip_through_json() {
+    let mut solver = Newuoa::for_dimension(3);
+    solver
+        .max_duration(Duration::from_millis(1500))
+        .ftol_rel(1e-9)
+        .non_finite_policy(NonFinitePolicy::Penalty(1e6))
+        .record_history(true)
+        .maximize(true)
+        .scaling(&[1.0, 2.0, 0.5])
+        .fix_variable(2, 0.25)
+        .checkpoint_every(10, "run.checkpoint")
+        .prior(Prior::gaussian(&[0.0, 1.0, 0.0], &[0.5, 0.5, 0.5]));
+    let configuration = solver.configuration();
+    let json = ::serde_json::to_string(&configuration).unwrap();
+    assert_eq!(::serde_json::from_str::<Configuration>(&json).unwrap(), configuration);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_configuration_deserialization_should_reject_invalid_settings() {
+    let valid = Newuoa::for_dimension(3).configuration();
+    let invalid = [
+        Settings {initial_trust_region_radius: 0.0, ..valid.settings.clone()},
+        Settings {final_trust_region_radius: f64::INFINITY, ..valid.settings.clone()},
+        Settings {final_trust_region_radius: 2.0*valid.settings.initial_trust_region_radius, ..valid.settings.clone()},
+        Settings {number_of_interpolation_conditions: 3, ..valid.settings.clone()},
+        Settings {number_of_interpolation_conditions: 11, ..valid.settings.clone()},
+        Settings {max_function_calls_count: 0, ..valid.settings.clone()},
+    ];
+    for settings in invalid.iter() {
+        let error = settings.check().unwrap_err();
+        let configuration = Configuration {settings: settings.clone(), ..valid.clone()};
+        let json = ::serde_json::to_string(&configuration).unwrap();
+        let result = ::serde_json::from_str::<Configuration>(&json);
+        assert!(result.unwrap_err().to_string().contains(&error.to_string()));
+    }
+}
@@ -0,0 +1,163 @@
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Domain {
+    Continuous,
+    /// Rounded to the nearest integer.
+    Integer,
+    /// Snapped to the nearest of the values.
+    Values(Vec<f64>),
+}
+
+impl Domain {
+    pub fn snap(&self, value: f64) -> f64 {
+        match *self {
+            Domain::Continuous => value,
+            Domain::Integer => value.round(),
+            Domain::Values(ref values) => values.iter().cloned()
+                .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+                .unwrap_or(value),
+        }
+    }
+}
+
+/// Heuristic mixed-variable layer: the solver searches a continuous space and every point
+/// is snapped to the domains before calling the objective. The objective is piecewise
+/// constant along snapped coordinates, so use an initial radius of at least the spacing
+/// of their values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Discrete {
+    domains: Vec<Domain>,
+}
+
+impl Discrete {
+    pub fn new() -> Discrete {
+        Discrete {domains: Vec::new()}
+    }
+
+    pub fn integer(&mut self, index: usize) -> &mut Self {
+        self.domain(index, Domain::Integer)
+    }
+
+    pub fn values(&mut self, index: usize, values: &[f64]) -> &mut Self {
+        assert!(!values.is_empty());
+        self.domain(index, Domain::Values(values.to_vec()))
+    }
+
+    pub fn domain(&mut self, index: usize, value: Domain) -> &mut Self {
+        if self.domains.len() <= index {
+            self.domains.resize(index + 1, Domain::Continuous);
+        }
+        self.domains[index] = value;
+        self
+    }
+
+    pub fn snap(&self, x: &[f64], result: &mut Vec<f64>) {
+        result.clear();
+        result.extend(x.iter().enumerate()
+            .map(|(i, v)| self.domains.get(i).map_or(*v, |d| d.snap(*v))));
+    }
+
+    /// Runs `solver` and reports the snapped solution, its value and the snapped history.
+    /// The incumbent was evaluated at its snapped point, so that point is normally the
+    /// solution and costs no extra call. Otherwise, e.g. when a prior moved the incumbent
+    /// to another cell, the snapped solution is evaluated while the budget allows it and the
+    /// best snapped point evaluated is reported when it does not or when it is better.
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let sign = if solver.maximize { -1.0 } else { 1.0 };
+        let mut point = Vec::with_capacity(values.len());
+        let mut best: Option<(Vec<f64>, f64)> = None;
+        let mut result = solver.perform_mut(values, &mut |x: &[f64]| {
+            self.snap(x, &mut point);
+            let value = function(&point);
+            if value.is_finite() && best.as_ref().is_none_or(|v| sign*value < sign*v.1) {
+                best = Some((point.clone(), value));
+            }
+            value
+        })?;
+        self.snap(&result.solution.clone(), &mut result.solution);
+        if let Some((point, value)) = best {
+            if result.solution != point {
+                let snapped_value = if result.function_calls_count < solver.max_function_calls_count {
+                    result.function_calls_count += 1;
+                    function(&result.solution)
+                } else {
+                    f64::NAN
+                };
+                if snapped_value.is_finite() && sign*snapped_value <= sign*value {
+                    result.value = snapped_value;
+                } else {
+                    result.solution = point;
+                    result.value = value;
+                }
+            }
+        }
+        values[..result.solution.len()].copy_from_slice(&result.solution);
+        for evaluation in result.history.iter_mut() {
+            self.snap(&evaluation.point.clone(), &mut evaluation.point);
+        }
+        Ok(result)
+    }
+}
+
+#[test]
+fn test_discrete_should_call_objective_with_snapped_values() {
+    let mut values = [0.0, 0.0];
+    let result = Discrete::new()
+        .integer(0)
+        .values(1, &[-1.0, 0.5, 3.0])
+        .perform_mut(Newuoa::new().max_function_calls_count(50), &mut values, &mut |x: &[f64]| {
+            assert_eq!(x[0], x[0].round());
+            assert!([-1.0, 0.5, 3.0].contains(&x[1]));
+            (x[0] - 2.3).powi(2) + (x[1] - 0.4).powi(2)
+        })
+        .unwrap();
+    assert_eq!(values[0], values[0].round());
+    assert_eq!(result.solution, values.to_vec());
+}
+
+#[test]
+fn test_discrete_should_not_evaluate_snapped_incumbent_again() {
+    let mut values = [0.0, 0.0];
+    let mut calls = 0;
+    let result = Discrete::new()
+        .integer(0)
+        .integer(1)
+        .perform_mut(Newuoa::new().max_function_calls_count(30), &mut values, &mut |x: &[f64]| {
+            calls += 1;
+            (x[0] - 2.3).powi(2) + (x[1] + 1.6).powi(2)
+        })
+        .unwrap();
+    assert_eq!(calls, result.function_calls_count);
+    assert!(result.function_calls_count <= 30);
+    assert_eq!(result.solution, vec![2.0, -2.0]);
+    assert_eq!(result.value, (2.0f64 - 2.3).powi(2) + (-2.0f64 + 1.6).powi(2));
+}
+
+#[test]
+fn test_discrete_should_keep_best_point_when_snapped_solution_is_worse() {
+    use prior::Prior;
+
+    let mut values = [3.0, -2.0];
+    let mut calls = Vec::new();
+    let result = Discrete::new()
+        .integer(0)
+        .integer(1)
+        .perform_mut(Newuoa::new().max_function_calls_count(100).final_trust_region_radius(0.1)
+                .prior(Prior::gaussian(&[0.0, 0.0], &[10.0, 10.0])),
+            &mut values, &mut |x: &[f64]| {
+                let value = (x[0] - 2.3).powi(2) + (x[1] + 1.6).powi(2);
+                calls.push((x.to_vec(), value));
+                value
+            })
+        .unwrap();
+    let best = calls.iter().min_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+    assert_ne!(calls.last().unwrap().0, best.0);
+    assert_eq!(result.solution, best.0);
+    assert_eq!(result.value, best.1);
+    assert_eq!(result.function_calls_count, calls.len());
+}
@@ -0,0 +1,71 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NewuoaError {
+    /// NEWUOA needs at least two variables, not counting fixed ones.
+    DimensionTooSmall {variables_count: usize},
+    ValuesTooShort {values_count: usize, variables_count: usize},
+    /// The number of interpolation conditions must lie in `[n + 2, (n + 1)(n + 2)/2]`.
+    InvalidInterpolationConditions {number_of_interpolation_conditions: usize, min: usize, max: usize},
+    /// Trust region radii must be positive and finite.
+    InvalidTrustRegionRadius {radius: f64},
+    /// The function calls budget must be at least one.
+    InvalidBudget {max_function_calls_count: usize},
+    /// `Newuoa::ftol_rel` and `Newuoa::ftol_abs` must not be negative.
+    InvalidTolerance {tolerance: f64},
+    /// Scaling factors must be positive and finite.
+    InvalidScaling {index: usize, factor: f64},
+    /// `Newuoa::checkpoint_every` needs at least one function call between checkpoints.
+    InvalidCheckpointPeriod,
+    /// The initial radius must not be smaller than the final one.
+    RadiusOrderInvalid {initial_trust_region_radius: f64, final_trust_region_radius: f64},
+    PriorDimensionMismatch {prior_len: usize, variables_count: usize},
+    ScalingDimensionMismatch {scaling_len: usize, variables_count: usize},
+    FixedVariableOutOfRange {index: usize, variables_count: usize},
+    /// `NonFinitePolicy::Penalty` must replace non-finite values with a finite one.
+    NonFinitePenalty {penalty: f64},
+    /// See `Newuoa::required_workspace_len`.
+    WorkspaceTooShort {workspace_len: usize, required_len: usize},
+}
+
+impl fmt::Display for NewuoaError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NewuoaError::DimensionTooSmall {variables_count} =>
+                write!(formatter, "variables count {} is less than 2", variables_count),
+            NewuoaError::ValuesTooShort {values_count, variables_count} =>
+                write!(formatter, "{} values given for {} variables", values_count, variables_count),
+            NewuoaError::InvalidInterpolationConditions {number_of_interpolation_conditions, min, max} =>
+                write!(formatter, "number of interpolation conditions {} is out of range [{}, {}]",
+                    number_of_interpolation_conditions, min, max),
+            NewuoaError::InvalidTrustRegionRadius {radius} =>
+                write!(formatter, "trust region radius {} is not positive and finite", radius),
+            NewuoaError::InvalidBudget {max_function_calls_count} =>
+                write!(formatter, "function calls budget {} is less than 1", max_function_calls_count),
+            NewuoaError::InvalidTolerance {tolerance} =>
+                write!(formatter, "function tolerance {} is negative", tolerance),
+            NewuoaError::InvalidScaling {index, factor} =>
+                write!(formatter, "scaling factor {} of variable {} is not positive and finite", factor,
+                    index),
+            NewuoaError::InvalidCheckpointPeriod =>
+                write!(formatter, "checkpoint period is zero function calls"),
+            NewuoaError::RadiusOrderInvalid {initial_trust_region_radius, final_trust_region_radius} =>
+                write!(formatter, "initial trust region radius {} is less than final {}",
+                    initial_trust_region_radius, final_trust_region_radius),
+            NewuoaError::PriorDimensionMismatch {prior_len, variables_count} =>
+                write!(formatter, "prior has {} variables instead of {}", prior_len, variables_count),
+            NewuoaError::ScalingDimensionMismatch {scaling_len, variables_count} =>
+                write!(formatter, "scaling has {} factors instead of {}", scaling_len, variables_count),
+            NewuoaError::FixedVariableOutOfRange {index, variables_count} =>
+                write!(formatter, "fixed variable {} is out of {} variables", index, variables_count),
+            NewuoaError::NonFinitePenalty {penalty} =>
+                write!(formatter, "non-finite values penalty {} is not finite", penalty),
+            NewuoaError::WorkspaceTooShort {workspace_len, required_len} =>
+                write!(formatter, "workspace has {} values instead of at least {}", workspace_len,
+                    required_len),
+        }
+    }
+}
+
+impl Error for NewuoaError {}
@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::ControlFlow;
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FallibleError<E> {
+    Solver(NewuoaError),
+    /// First error of the objective, which was not called anymore, with the best point
+    /// found before it and `TerminationReason::ObjectiveFailed`.
+    Objective {error: E, incumbent: Box<OptimizationResult>},
+}
+
+impl<E: fmt::Display> fmt::Display for FallibleError<E> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FallibleError::Solver(ref error) => write!(formatter, "{}", error),
+            FallibleError::Objective {ref error, ..} => write!(formatter, "objective failed: {}", error),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for FallibleError<E> {}
+
+impl Newuoa {
+    /// `perform_mut` for objectives that can fail, the run stops at the first error like
+    /// an observer stopping it, and the error is returned instead of the result.
+    pub fn perform_fallible<E, F>(&mut self, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, FallibleError<E>>
+            where F: FnMut(&[f64]) -> Result<f64, E> {
+        self.validate(values).map_err(FallibleError::Solver)?;
+        self.resize_working_space();
+        let mut working_space = std::mem::take(&mut self.working_space);
+        let mut error = None;
+        let result = self.perform_stoppable(&mut working_space, values, &mut |x: &[f64]| {
+            match function(x) {
+                Ok(value) => ControlFlow::Continue(value),
+                Err(e) => {
+                    error = Some(e);
+                    ControlFlow::Break(())
+                }
+            }
+        });
+        self.working_space = working_space;
+        let result = result.map_err(FallibleError::Solver)?;
+        match error {
+            Some(error) => Err(FallibleError::Objective {error, incumbent: Box::new(result)}),
+            None => Ok(result),
+        }
+    }
+}
+
+#[test]
+fn test_perform_fallible_should_stop_at_first_error() {
+    use result::TerminationReason;
+    let mut calls_count = 0;
+    let mut values = [1.0, 1.0];
+    let result = Newuoa::new()
+        .max_function_calls_count(100)
+        .perform_fallible(&mut values, &mut |x: &[f64]| {
+            calls_count += 1;
+            if calls_count == 5 { Err("simulator crashed") } else { Ok(x[0]*x[0] + x[1]*x[1]) }
+        });
+    let (error, incumbent) = match result {
+        Err(FallibleError::Objective {error, incumbent}) => (error, incumbent),
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(error, "simulator crashed");
+    assert_eq!(calls_count, 5);
+    assert_eq!(incumbent.termination_reason, TerminationReason::ObjectiveFailed);
+    assert_eq!(incumbent.function_calls_count, 5);
+    assert_eq!(incumbent.solution, values.to_vec());
+    assert!(incumbent.value < 2.0);
+}
@@ -0,0 +1,145 @@
+use Newuoa;
+use error::NewuoaError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Weighting {
+    Uniform,
+    /// Weights each sample by its share of the log-frequency axis, so every decade
+    /// contributes equally regardless of how densely it is sampled.
+    LogFrequency,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BandFit {
+    pub lower: f64,
+    pub upper: f64,
+    pub samples_count: usize,
+    pub rms_error: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FitReport {
+    pub objective: f64,
+    pub bands: Vec<BandFit>,
+}
+
+/// Fits a parametric response model `model(parameters, x)` to sampled `(x, y)` target
+/// data by weighted least squares, where `x` is a frequency or a time.
+pub struct FilterFit {
+    weighting: Weighting,
+    band_edges: Vec<f64>,
+}
+
+impl Default for FilterFit {
+    fn default() -> FilterFit {
+        FilterFit::new()
+    }
+}
+
+impl FilterFit {
+    pub fn new() -> FilterFit {
+        FilterFit {
+            weighting: Weighting::Uniform,
+            band_edges: Vec::new(),
+        }
+    }
+
+    pub fn weighting(&mut self, value: Weighting) -> &mut Self {
+        self.weighting = value;
+        self
+    }
+
+    pub fn band_edges(&mut self, value: &[f64]) -> &mut Self {
+        assert!(value.windows(2).all(|w| w[0] < w[1]));
+        self.band_edges = value.to_vec();
+        self
+    }
+
+    pub fn objective<M>(&self, parameters: &[f64], target: &[(f64, f64)], model: &M) -> f64
+            where M: Fn(&[f64], f64) -> f64 {
+        self.weights(target).iter().zip(target.iter())
+            .map(|(w, &(x, y))| {
+                let residual = model(parameters, x) - y;
+                w*residual*residual
+            })
+            .sum()
+    }
+
+    pub fn fit<M>(&self, solver: &mut Newuoa, parameters: &mut [f64], target: &[(f64, f64)],
+            model: &M) -> Result<FitReport, NewuoaError>
+            where M: Fn(&[f64], f64) -> f64 {
+        let n = parameters.len();
+        let objective = {
+            let function = |x: &[f64]| self.objective(x, target, model);
+            solver
+                .variables_count(n)
+                .number_of_interpolation_conditions(2*n + 1)
+                .perform(parameters, &function)?
+                .value
+        };
+        Ok(FitReport {objective, bands: self.bands(parameters, target, model)})
+    }
+
+    pub fn bands<M>(&self, parameters: &[f64], target: &[(f64, f64)], model: &M) -> Vec<BandFit>
+            where M: Fn(&[f64], f64) -> f64 {
+        self.band_edges.windows(2)
+            .map(|edges| {
+                let mut samples_count = 0;
+                let mut squares = 0.0;
+                for &(x, y) in target.iter().filter(|v| v.0 >= edges[0] && v.0 < edges[1]) {
+                    let residual = model(parameters, x) - y;
+                    squares += residual*residual;
+                    samples_count += 1;
+                }
+                let rms_error = if samples_count > 0 {
+                    (squares/samples_count as f64).sqrt()
+                } else {
+                    0.0
+                };
+                BandFit {lower: edges[0], upper: edges[1], samples_count, rms_error}
+            })
+            .collect()
+    }
+
+    fn weights(&self, target: &[(f64, f64)]) -> Vec<f64> {
+        match self.weighting {
+            Weighting::Uniform => vec![1.0; target.len()],
+            Weighting::LogFrequency => {
+                let logs = target.iter()
+                    .map(|v| {
+                        assert!(v.0 > 0.0);
+                        v.0.ln()
+                    })
+                    .collect::<Vec<_>>();
+                (0..logs.len())
+                    .map(|i| {
+                        let previous = logs[if i > 0 { i - 1 } else { i }];
+                        let next = logs[if i + 1 < logs.len() { i + 1 } else { i }];
+                        (next - previous).abs()/2.0
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[test]
+fn test_log_frequency_weights_should_cover_log_axis() {
+    let target = [(10.0, 0.0), (100.0, 0.0), (1000.0, 0.0), (1100.0, 0.0)];
+    let mut fit = FilterFit::new();
+    fit.weighting(Weighting::LogFrequency);
+    let total = fit.weights(&target).iter().sum::<f64>();
+    assert!((total - (1100.0_f64.ln() - 10.0_f64.ln())).abs() < 1e-12);
+}
+
+#[test]
+fn test_bands_should_report_error_per_band() {
+    let target = [(1.0, 1.0), (2.0, 2.0), (3.0, 5.0), (4.0, 4.0)];
+    let mut fit = FilterFit::new();
+    fit.band_edges(&[0.0, 2.5, 5.0]);
+    let bands = fit.bands(&[1.0], &target, &|p: &[f64], x: f64| p[0]*x);
+    assert_eq!(bands[0].samples_count, 2);
+    assert_eq!(bands[0].rms_error, 0.0);
+    assert_eq!(bands[1].samples_count, 2);
+    assert_eq!(bands[1].rms_error, 2.0_f64.sqrt());
+}
@@ -0,0 +1,35 @@
+use std::convert::TryFrom;
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+impl Newuoa {
+    /// `perform_mut` for a dimension known at compile time, with the variables count set to
+    /// `N` so the objective and the starting point cannot disagree on it.
+    pub fn perform_fixed<F, const N: usize>(&mut self, values: &mut [f64; N], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64; N]) -> f64 {
+        self.variables_count(N).perform_mut(values, &mut |x: &[f64]| {
+            function(<&[f64; N]>::try_from(x).expect("point of a different dimension"))
+        })
+    }
+
+    /// Working space length `perform_fixed` needs for `N` variables, usable in constants.
+    pub const fn fixed_working_space_size<const N: usize>(number_of_interpolation_conditions: usize)
+            -> usize {
+        Newuoa::required_workspace_len(N, number_of_interpolation_conditions)
+    }
+}
+
+#[test]
+fn test_perform_fixed_should_write_solution_back() {
+    const SIZE: usize = Newuoa::fixed_working_space_size::<2>(5);
+    assert_eq!(SIZE, 3*2*5/2 + 18*7);
+    let mut values = [1.0, 1.0];
+    let result = Newuoa::new()
+        .max_function_calls_count(20)
+        .perform_fixed(&mut values, &mut |x: &[f64; 2]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(values.to_vec(), result.solution);
+}
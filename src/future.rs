@@ -0,0 +1,68 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use Newuoa;
+use ask_tell::AskTell;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+/// Run awaiting an asynchronous objective, see `Newuoa::perform_async`. The native routine
+/// works on a background thread through `AskTell`, which wakes the task once it has the
+/// next point, so polling never blocks the executor.
+pub struct PerformFuture<F, R> {
+    ask_tell: AskTell,
+    function: F,
+    pending: Option<Pin<Box<R>>>,
+}
+
+impl<F, R> PerformFuture<F, R> where F: FnMut(&[f64]) -> R, R: Future<Output = f64> {
+    pub fn new(ask_tell: AskTell, function: F) -> PerformFuture<F, R> {
+        PerformFuture {ask_tell, function, pending: None}
+    }
+}
+
+impl<F, R> Future for PerformFuture<F, R>
+        where F: FnMut(&[f64]) -> R + Unpin, R: Future<Output = f64> {
+    type Output = OptimizationResult;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<OptimizationResult> {
+        let this = self.get_mut();
+        loop {
+            if let Some(ref mut pending) = this.pending {
+                match pending.as_mut().poll(context) {
+                    Poll::Ready(value) => this.ask_tell.tell(value),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            this.pending = None;
+            match this.ask_tell.poll_ask(context) {
+                Poll::Ready(Some(x)) => this.pending = Some(Box::pin((this.function)(x))),
+                Poll::Ready(None) => return Poll::Ready(this.ask_tell.result().cloned()
+                    .expect("settings are validated by Newuoa::ask_tell")),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Newuoa {
+    /// Run awaiting `function` for every evaluation, e.g. a request to a simulation
+    /// service. Like `ask_tell` it runs a copy of the solver without the observer.
+    pub fn perform_async<F, R>(&self, values: &[f64], function: F)
+            -> Result<PerformFuture<F, R>, NewuoaError>
+            where F: FnMut(&[f64]) -> R, R: Future<Output = f64> {
+        self.ask_tell(values).map(|v| PerformFuture::new(v, function))
+    }
+}
+
+#[test]
+fn test_perform_async_should_await_objective() {
+    use std::future::ready;
+    let future = Newuoa::new()
+        .max_function_calls_count(10)
+        .perform_async(&[1.0, 1.0], |x: &[f64]| ready(x[0]*x[0] + x[1]*x[1]))
+        .unwrap();
+    let result = ::futures::executor::block_on(future);
+    assert_eq!(result.function_calls_count, 10);
+}
@@ -0,0 +1,147 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use observer::Diagnostics;
+
+/// Objective wrapper counting calls and measuring their duration. Stable Rust does not
+/// allow implementing the `Fn*` traits, so pass `as_fn_mut()` (or a closure calling
+/// `call`) wherever an `FnMut(&[f64]) -> f64` is expected. `Newuoa` runs wrap their
+/// objective in one to report `OptimizationResult::objective_duration`.
+pub struct Instrumented<F> {
+    function: F,
+    calls_count: usize,
+    total_duration: Duration,
+    last_duration: Duration,
+    logged: bool,
+    writer: Option<Diagnostics>,
+}
+
+impl<F> Instrumented<F> where F: FnMut(&[f64]) -> f64 {
+    pub fn new(function: F) -> Instrumented<F> {
+        Instrumented {
+            function,
+            calls_count: 0,
+            total_duration: Duration::new(0, 0),
+            last_duration: Duration::new(0, 0),
+            logged: false,
+            writer: None,
+        }
+    }
+
+    /// Records every call with its arguments, result and duration as a debug `log` record
+    /// and `tracing` event, for the enabled features.
+    pub fn logged(mut self, value: bool) -> Self {
+        self.logged = value;
+        self
+    }
+
+    /// Writes every call as a line to `writer`, write errors being ignored.
+    pub fn log_to<W>(mut self, writer: W) -> Self where W: Write + Send + 'static {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    pub fn call(&mut self, x: &[f64]) -> f64 {
+        let start = Instant::now();
+        let result = (self.function)(x);
+        self.last_duration = start.elapsed();
+        self.total_duration += self.last_duration;
+        self.calls_count += 1;
+        #[cfg(feature = "log")]
+        if self.logged {
+            log::debug!("call {}: f({:?}) = {} in {:?}", self.calls_count, x, result, self.last_duration);
+        }
+        #[cfg(feature = "tracing")]
+        if self.logged {
+            tracing::debug!(call = self.calls_count, x = ?x, f = result, duration = ?self.last_duration);
+        }
+        if let Some(ref mut writer) = self.writer {
+            let _ = writeln!(writer, "call {}: f({:?}) = {} in {:?}", self.calls_count, x, result,
+                self.last_duration);
+        }
+        result
+    }
+
+    pub fn as_fn_mut<'a>(&'a mut self) -> impl FnMut(&[f64]) -> f64 + 'a where F: 'a {
+        move |x: &[f64]| self.call(x)
+    }
+
+    pub fn calls_count(&self) -> usize {
+        self.calls_count
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.total_duration
+    }
+
+    pub fn last_duration(&self) -> Duration {
+        self.last_duration
+    }
+
+    pub fn mean_duration(&self) -> Duration {
+        if self.calls_count == 0 {
+            Duration::new(0, 0)
+        } else {
+            self.total_duration.div_f64(self.calls_count as f64)
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.calls_count = 0;
+        self.total_duration = Duration::new(0, 0);
+        self.last_duration = Duration::new(0, 0);
+    }
+
+    pub fn into_inner(self) -> F {
+        self.function
+    }
+}
+
+#[test]
+fn test_instrumented_should_count_calls() {
+    let mut instrumented = Instrumented::new(|x: &[f64]| x[0]*x[0]);
+    {
+        let mut function = instrumented.as_fn_mut();
+        assert_eq!(function(&[2.0]), 4.0);
+        assert_eq!(function(&[3.0]), 9.0);
+    }
+    assert_eq!(instrumented.call(&[1.0]), 1.0);
+    assert_eq!(instrumented.calls_count(), 3);
+}
+
+#[test]
+fn test_instrumented_should_log_calls_to_writer() {
+    use std::sync::{Arc, Mutex};
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+    impl Write for Shared {
+        fn write(&mut self, buffer: &[u8]) -> ::std::io::Result<usize> {
+            self.0.lock().unwrap().write(buffer)
+        }
+        fn flush(&mut self) -> ::std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let mut instrumented = Instrumented::new(|x: &[f64]| x[0]*x[0]).log_to(Shared(output.clone()));
+    instrumented.call(&[2.0]);
+    instrumented.call(&[3.0]);
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with("call 2: f([3.0]) = 9 in "));
+}
+
+#[test]
+fn test_solver_should_measure_objective_duration() {
+    use Newuoa;
+
+    let mut values = [1.0, 1.0];
+    let result = Newuoa::new()
+        .max_function_calls_count(10)
+        .perform_mut(&mut values, &mut |x: &[f64]| {
+            ::std::thread::sleep(Duration::from_millis(1));
+            x[0]*x[0] + x[1]*x[1]
+        })
+        .unwrap();
+    assert!(result.objective_duration >= Duration::from_millis(result.function_calls_count as u64));
+}
@@ -0,0 +1,103 @@
+//! Objectives served by another process over a byte stream, e.g. a TCP or Unix socket.
+//! Each evaluation sends a request frame with the number of coordinates as a little endian
+//! `u64` followed by the coordinates as little endian `f64`, and reads the value back as a
+//! single little endian `f64`. The server sees the end of the stream once the client is
+//! dropped.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::Path;
+
+use Newuoa;
+use fallible::FallibleError;
+use result::OptimizationResult;
+
+pub struct RemoteObjective<S> {
+    stream: S,
+    buffer: Vec<u8>,
+}
+
+impl RemoteObjective<TcpStream> {
+    pub fn connect<A>(address: A) -> io::Result<RemoteObjective<TcpStream>> where A: ToSocketAddrs {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        Ok(RemoteObjective::new(stream))
+    }
+}
+
+#[cfg(unix)]
+impl RemoteObjective<UnixStream> {
+    pub fn connect_unix<P>(path: P) -> io::Result<RemoteObjective<UnixStream>> where P: AsRef<Path> {
+        UnixStream::connect(path).map(RemoteObjective::new)
+    }
+}
+
+impl<S> RemoteObjective<S> where S: Read + Write {
+    pub fn new(stream: S) -> RemoteObjective<S> {
+        RemoteObjective {stream, buffer: Vec::new()}
+    }
+
+    pub fn evaluate(&mut self, x: &[f64]) -> io::Result<f64> {
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&(x.len() as u64).to_le_bytes());
+        for v in x {
+            self.buffer.extend_from_slice(&v.to_le_bytes());
+        }
+        self.stream.write_all(&self.buffer)?;
+        self.stream.flush()?;
+        let mut value = [0; 8];
+        self.stream.read_exact(&mut value)?;
+        Ok(f64::from_le_bytes(value))
+    }
+
+    /// Runs `solver` on the remote objective, stopping at the first I/O error.
+    pub fn perform_mut(&mut self, solver: &mut Newuoa, values: &mut [f64])
+            -> Result<OptimizationResult, FallibleError<io::Error>> {
+        solver.perform_fallible(values, &mut |x: &[f64]| self.evaluate(x))
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+/// Server side of the protocol: answers requests with `function` until the client closes
+/// the stream.
+pub fn serve<S, F>(mut stream: S, mut function: F) -> io::Result<()>
+        where S: Read + Write, F: FnMut(&[f64]) -> f64 {
+    let mut point = Vec::new();
+    loop {
+        let mut header = [0; 8];
+        match stream.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        point.clear();
+        for _ in 0..u64::from_le_bytes(header) {
+            let mut value = [0; 8];
+            stream.read_exact(&mut value)?;
+            point.push(f64::from_le_bytes(value));
+        }
+        stream.write_all(&function(&point).to_le_bytes())?;
+        stream.flush()?;
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_remote_objective_should_evaluate_through_socket() {
+    use std::thread;
+    let (client, server) = UnixStream::pair().unwrap();
+    let server = thread::spawn(move || serve(server, |x: &[f64]| x[0]*x[0] + x[1]*x[1]));
+    let mut objective = RemoteObjective::new(client);
+    assert_eq!(objective.evaluate(&[1.0, 2.0]).unwrap(), 5.0);
+    let result = objective.perform_mut(Newuoa::new().max_function_calls_count(20), &mut [1.0, 1.0])
+        .unwrap();
+    assert_eq!(result.function_calls_count, 20);
+    drop(objective);
+    server.join().unwrap().unwrap();
+}
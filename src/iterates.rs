@@ -0,0 +1,139 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use Newuoa;
+use ask_tell::AskTell;
+use error::NewuoaError;
+use metadata::{RunId, RunMetadata};
+use progress::Progress;
+
+/// Incumbent after an evaluation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Iterate {
+    pub point: Vec<f64>,
+    pub value: f64,
+    /// Estimate of the trust region radius, see `Progress::rho`.
+    pub rho: f64,
+    pub function_calls_count: usize,
+    pub run_id: RunId,
+}
+
+impl Iterate {
+    /// Runs `solver` starting from this incumbent, typically configured with smaller
+    /// radii to polish a previous result. Returns the better of this incumbent and the
+    /// final one of the new run, with function calls counted across both runs. Only the
+    /// point is carried over: the native routine rebuilds its interpolation model.
+    pub fn continue_with<F>(&self, solver: &Newuoa, function: &mut F) -> Result<Iterate, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let result = match solver.iterates(&self.point, function)?.last() {
+            Some(mut last) => {
+                last.function_calls_count += self.function_calls_count;
+                let better = if solver.maximize { last.value >= self.value } else { last.value <= self.value };
+                if better {
+                    last
+                } else {
+                    Iterate {function_calls_count: last.function_calls_count, ..self.clone()}
+                }
+            }
+            None => self.clone(),
+        };
+        Ok(result)
+    }
+}
+
+/// Iterator over incumbents produced by `Newuoa::iterates`, evaluating the objective on
+/// the calling thread. Stopping the iteration early abandons the run. Incumbents are the
+/// largest values when maximizing.
+pub struct Iterates<F> {
+    ask_tell: AskTell,
+    function: F,
+    progress: Progress,
+    sign: f64,
+}
+
+impl<F> Iterates<F> where F: FnMut(&[f64]) -> f64 {
+    pub fn new(ask_tell: AskTell, function: F, initial_trust_region_radius: f64, maximize: bool)
+            -> Iterates<F> {
+        Iterates {
+            ask_tell,
+            function,
+            progress: Progress::new(initial_trust_region_radius),
+            sign: if maximize { -1.0 } else { 1.0 },
+        }
+    }
+}
+
+impl<F> Iterates<F> {
+    pub fn metadata(&self) -> &RunMetadata {
+        self.ask_tell.metadata()
+    }
+}
+
+impl<F> Iterator for Iterates<F> where F: FnMut(&[f64]) -> f64 {
+    type Item = Iterate;
+
+    fn next(&mut self) -> Option<Iterate> {
+        let x = match self.ask_tell.ask() {
+            Some(x) => x.to_vec(),
+            None => return None,
+        };
+        let value = (self.function)(&x);
+        self.ask_tell.tell(value);
+        self.progress.update(&x, self.sign*value);
+        Some(Iterate {
+            point: self.progress.best_point.clone(),
+            value: self.sign*self.progress.best_value,
+            rho: self.progress.rho,
+            function_calls_count: self.progress.function_calls_count,
+            run_id: self.ask_tell.metadata().id,
+        })
+    }
+}
+
+#[test]
+fn test_iterates_should_yield_non_increasing_values() {
+    let iterates = Newuoa::new()
+        .max_function_calls_count(10)
+        .iterates(&[1.0, 2.0], |x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert!(!iterates.is_empty());
+    for (i, pair) in iterates.windows(2).enumerate() {
+        assert!(pair[1].value <= pair[0].value);
+        assert_eq!(pair[1].function_calls_count, i + 2);
+    }
+}
+
+#[test]
+fn test_continue_with_should_not_make_incumbent_worse() {
+    let mut function = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    let first = Newuoa::new()
+        .max_function_calls_count(10)
+        .iterates(&[1.0, 2.0], &mut function)
+        .unwrap()
+        .last()
+        .unwrap();
+    let polished = first.continue_with(Newuoa::new()
+        .initial_trust_region_radius(1e-2)
+        .final_trust_region_radius(1e-8)
+        .max_function_calls_count(10), &mut function).unwrap();
+    assert!(polished.value <= first.value);
+    assert!(polished.function_calls_count > first.function_calls_count);
+}
+
+#[test]
+fn test_iterates_should_keep_largest_values_when_maximizing() {
+    let mut function = |x: &[f64]| -x[0]*x[0] - x[1]*x[1];
+    let mut solver = Newuoa::new();
+    solver.maximize(true).max_function_calls_count(10);
+    let iterates = solver.iterates(&[1.0, 2.0], &mut function).unwrap().collect::<Vec<_>>();
+    assert_eq!(iterates[0].value, -5.0);
+    for pair in iterates.windows(2) {
+        assert!(pair[1].value >= pair[0].value);
+    }
+    let last = iterates.last().unwrap();
+    assert_eq!(function(&last.point), last.value);
+    let polished = last.continue_with(&solver, &mut function).unwrap();
+    assert!(polished.value >= last.value);
+}
@@ -0,0 +1,91 @@
+use Newuoa;
+use error::NewuoaError;
+use minimize::Options;
+use result::OptimizationResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeastSquaresReport {
+    pub result: OptimizationResult,
+    /// Residuals at the solution.
+    pub residuals: Vec<f64>,
+    pub sum_of_squares: f64,
+}
+
+/// Minimizes the sum of squares of a residual vector.
+pub struct LeastSquares<R> {
+    residuals: R,
+}
+
+impl<R> LeastSquares<R> where R: FnMut(&[f64]) -> Vec<f64> {
+    pub fn new(residuals: R) -> LeastSquares<R> {
+        LeastSquares {residuals}
+    }
+
+    pub fn sum_of_squares(&mut self, x: &[f64]) -> f64 {
+        (self.residuals)(x).iter().map(|r| r*r).sum()
+    }
+
+    /// Runs `solver` with `2n + 1` interpolation conditions for the `n` values.
+    pub fn perform_mut(&mut self, solver: &mut Newuoa, values: &mut [f64])
+            -> Result<LeastSquaresReport, NewuoaError> {
+        let n = values.len();
+        let result = solver
+            .variables_count(n)
+            .number_of_interpolation_conditions(2*n + 1)
+            .perform_mut(values, &mut |x: &[f64]| self.sum_of_squares(x))?;
+        let residuals = (self.residuals)(values);
+        Ok(LeastSquaresReport {
+            result,
+            sum_of_squares: residuals.iter().map(|r| r*r).sum(),
+            residuals,
+        })
+    }
+
+    pub fn into_inner(self) -> R {
+        self.residuals
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurveFit {
+    pub parameters: Vec<f64>,
+    /// Euclidean norm of `model(parameters, xs[i]) - ys[i]` at the fitted parameters.
+    pub residual_norm: f64,
+    pub result: OptimizationResult,
+}
+
+/// Fits `model(parameters, x)` to the points `(xs[i], ys[i])` from `parameters`, which
+/// receive the fit, with the settings `minimize` picks by default.
+pub fn fit<M>(model: M, xs: &[f64], ys: &[f64], parameters: &mut [f64])
+        -> Result<CurveFit, NewuoaError>
+        where M: Fn(&[f64], f64) -> f64 {
+    assert_eq!(xs.len(), ys.len());
+    let mut least_squares = LeastSquares::new(|p: &[f64]| {
+        xs.iter().zip(ys).map(|(x, y)| model(p, *x) - y).collect()
+    });
+    let report = least_squares.perform_mut(&mut Options::default().solver(parameters)?, parameters)?;
+    Ok(CurveFit {
+        parameters: parameters.to_vec(),
+        residual_norm: report.sum_of_squares.sqrt(),
+        result: report.result,
+    })
+}
+
+#[test]
+fn test_least_squares_should_report_residuals_at_solution() {
+    let mut least_squares = LeastSquares::new(|x: &[f64]| vec![x[0] - 1.0, x[1] + 2.0, x[0] + x[1] + 1.0]);
+    let mut values = [0.0, 0.0];
+    let report = least_squares.perform_mut(&mut Newuoa::new(), &mut values).unwrap();
+    assert_eq!(report.residuals, vec![values[0] - 1.0, values[1] + 2.0, values[0] + values[1] + 1.0]);
+    assert_eq!(report.sum_of_squares, report.result.value);
+}
+
+#[test]
+fn test_fit_should_return_residual_norm_of_parameters() {
+    let xs = [0.0, 1.0, 2.0, 3.0];
+    let ys = [1.0, 3.1, 4.9, 7.0];
+    let model = |p: &[f64], x: f64| p[0] + p[1]*x;
+    let fit = fit(model, &xs, &ys, &mut [0.0, 0.0]).unwrap();
+    let expected = xs.iter().zip(&ys).map(|(x, y)| (model(&fit.parameters, *x) - y).powi(2)).sum::<f64>();
+    assert_eq!(fit.residual_norm, expected.sqrt());
+}
@@ -1,52 +1,117 @@
-use std::os::raw::c_void;
+#[cfg(feature = "argmin")]
+extern crate argmin;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(feature = "nalgebra")]
+extern crate nalgebra;
+#[cfg(feature = "ndarray")]
+extern crate ndarray;
+#[cfg(feature = "python")]
+extern crate pyo3;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "zstd")]
+extern crate zstd;
 
-type Function = fn(data: *const c_void, n: i64, x: *const f64) -> f64;
+#[cfg(feature = "argmin")]
+pub mod argmin_solver;
+#[cfg(feature = "ndarray")]
+mod array;
+pub mod ask_tell;
+pub mod basin_hopping;
+pub mod batch;
+pub mod builder;
+pub mod cache;
+pub mod calibration;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod checkpoint;
+pub mod combinators;
+pub mod compression;
+pub mod configuration;
+pub mod discrete;
+pub mod error;
+pub mod fallible;
+pub mod filter;
+mod fixed;
+#[cfg(feature = "async")]
+pub mod future;
+pub mod instrumented;
+pub mod ipc;
+pub mod iterates;
+pub mod least_squares;
+mod linalg;
+pub mod metadata;
+pub mod minimize;
+pub mod mle;
+pub mod multistart;
+pub mod noisy;
+pub mod objective;
+pub mod observer;
+mod parallel;
+pub mod penalty;
+pub mod pid;
+pub mod portfolio;
+pub mod prior;
+#[cfg(feature = "python")]
+mod python;
+mod progress;
+pub mod quadratic;
+mod random;
+pub mod restarts;
+pub mod result;
+mod run;
+pub mod scalar;
+pub mod screening;
+pub mod sequential;
+mod sobol;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod sys;
+pub mod transforms;
+pub mod types;
+#[cfg(feature = "nalgebra")]
+mod vector;
+mod wakeup;
+pub mod warnings;
+pub mod workspace;
 
-#[repr(C)]
-struct Closure {
-    data: *const c_void,
-    function: Function,
-}
-
-impl Closure {
-    pub fn new<F>(function: &F) -> Closure where F: Fn(&[f64]) -> f64 {
-        fn wrap<F>(closure: *const c_void, n: i64, x: *const f64) -> f64
-                where F: Fn(&[f64]) -> f64 {
-            use std::slice::from_raw_parts;
-            let closure = closure as *const F;
-            unsafe { (*closure)(from_raw_parts(x, n as usize)) }
-        }
-        Closure {data: &*function as *const _ as *const c_void, function: wrap::<F>}
-    }
-}
-
-type FunctionMut = fn(data: *mut c_void, n: i64, x: *const f64) -> f64;
-
-#[repr(C)]
-struct ClosureMut {
-    data: *mut c_void,
-    function: FunctionMut,
-}
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::panic::resume_unwind;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
 
-impl ClosureMut {
-    pub fn new<F>(function: &mut F) -> ClosureMut where F: FnMut(&[f64]) -> f64 {
-        fn wrap<F>(closure: *mut c_void, n: i64, x: *const f64) -> f64
-                where F: FnMut(&[f64]) -> f64 {
-            use std::slice::from_raw_parts;
-            let closure = closure as *mut F;
-            unsafe { (*closure)(from_raw_parts(x, n as usize)) }
-        }
-        ClosureMut {data: &mut *function as *mut _ as *mut c_void, function: wrap::<F>}
-    }
-}
-
-extern "C" {
-    fn newuoa_closure(function: *mut ClosureMut, n: i64, npt: i64, x: *mut f64,
-        rhobeg: f64, rhoend: f64, maxfun: i64, w: *mut f64) -> f64;
+use ask_tell::AskTell;
+use checkpoint::{Checkpoint, CheckpointError};
+use configuration::Configuration;
+use error::NewuoaError;
+use instrumented::Instrumented;
+use iterates::Iterates;
+use metadata::{RunMetadata, Settings};
+use observer::{Diagnostics, IntoControlFlow, IterationInfo, IterationUpdate, Observer};
+use prior::Prior;
+use result::{Evaluation, OptimizationResult, RadiusChange, TerminationReason};
+use run::{report, Run, RunOptions, Space};
+#[cfg(feature = "async")]
+use stream::IterateStream;
+use sys::{newuoa_closure, ClosureMut};
+use types::{Budget, NonFinitePolicy, Radius, Verbosity};
+use warnings::Warning;
 
-    fn newuoa_closure_const(function: *const Closure, n: i64, npt: i64, x: *mut f64,
-        rhobeg: f64, rhoend: f64, maxfun: i64, w: *mut f64) -> f64;
-}
+pub use minimize::minimize;
 
 pub struct Newuoa {
     variables_count: usize,
@@ -54,139 +119,1130 @@ pub struct Newuoa {
     initial_trust_region_radius: f64,
     final_trust_region_radius: f64,
     max_function_calls_count: usize,
+    options: RunOptions,
+    maximize: bool,
+    scaling: Option<Vec<f64>>,
+    fixed_variables: Vec<(usize, f64)>,
+    checkpoint: Option<(usize, PathBuf)>,
+    prior: Option<Prior>,
+    observer: Option<Observer>,
+    diagnostics: Option<Diagnostics>,
+    last_run: Option<RunMetadata>,
     working_space: Vec<f64>,
 }
 
+impl Default for Newuoa {
+    fn default() -> Newuoa {
+        Newuoa::new()
+    }
+}
+
+/// Clones every option except the observer, which cannot be cloned.
+impl Clone for Newuoa {
+    fn clone(&self) -> Newuoa {
+        self.detached()
+    }
+}
+
+impl fmt::Debug for Newuoa {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.debug_struct("Newuoa")
+            .field("configuration", &self.configuration())
+            .field("observer", &self.observer.is_some())
+            .field("diagnostics", &self.diagnostics.is_some())
+            .finish()
+    }
+}
+
 impl Newuoa {
     pub fn new() -> Newuoa {
-        use std::iter::repeat;
         const VARIABLES_COUNT: usize = 2;
         const NUMBER_OF_INTERPOLATION_CONDITIONS: usize = VARIABLES_COUNT + 2;
-        let working_space_size = Newuoa::working_space_size(
-            VARIABLES_COUNT,
-            NUMBER_OF_INTERPOLATION_CONDITIONS);
-        Newuoa {
+        let mut result = Newuoa {
             variables_count: VARIABLES_COUNT,
             number_of_interpolation_conditions: NUMBER_OF_INTERPOLATION_CONDITIONS,
-            initial_trust_region_radius: 1e-6,
-            final_trust_region_radius: 1e6,
+            initial_trust_region_radius: 1.0,
+            final_trust_region_radius: 1e-6,
             max_function_calls_count: 1000,
-            working_space: repeat(0.0).take(working_space_size).collect::<_>(),
-        }
+            options: RunOptions::default(),
+            maximize: false,
+            scaling: None,
+            fixed_variables: Vec::new(),
+            checkpoint: None,
+            prior: None,
+            observer: None,
+            diagnostics: None,
+            last_run: None,
+            working_space: Vec::new(),
+        };
+        result.resize_working_space();
+        result
+    }
+
+    /// Solver for `variables_count` variables with Powell's recommended `2n + 1`
+    /// interpolation conditions and a budget of `500n` calls, the working space already
+    /// allocated. The radii are the ones of `new`, suited to unit-scaled variables.
+    pub fn for_dimension(variables_count: usize) -> Newuoa {
+        let mut result = Newuoa::new();
+        result
+            .variables_count(variables_count)
+            .number_of_interpolation_conditions((2*variables_count + 1).max(4))
+            .max_function_calls_count((500*variables_count).max(1));
+        result.resize_working_space();
+        result
+    }
+
+    pub fn from_settings(settings: &Settings) -> Newuoa {
+        let mut result = Newuoa::new();
+        result
+            .variables_count(settings.variables_count)
+            .number_of_interpolation_conditions(settings.number_of_interpolation_conditions)
+            .initial_trust_region_radius(settings.initial_trust_region_radius)
+            .final_trust_region_radius(settings.final_trust_region_radius)
+            .max_function_calls_count(settings.max_function_calls_count);
+        result
+    }
+
+    /// Solver with every option of the configuration, which has no observer.
+    pub fn from_configuration(configuration: &Configuration) -> Newuoa {
+        let mut result = Newuoa::from_settings(&configuration.settings);
+        result.options = RunOptions {
+            max_duration: configuration.max_duration,
+            stop_at_objective: configuration.stop_at_objective,
+            ftol_rel: configuration.ftol_rel,
+            ftol_abs: configuration.ftol_abs,
+            non_finite_policy: configuration.non_finite_policy,
+            record_history: configuration.record_history,
+            verbosity: configuration.verbosity,
+        };
+        result.maximize = configuration.maximize;
+        result.scaling = configuration.scaling.clone();
+        result.fixed_variables = configuration.fixed_variables.clone();
+        result.fixed_variables.sort_by_key(|v| v.0);
+        result.checkpoint = configuration.checkpoint.clone();
+        result.prior = configuration.prior.clone();
+        result
     }
 
     pub fn variables_count(&mut self, value: usize) -> &mut Self {
-        assert!(value >= 2);
         self.variables_count = value;
         self
     }
 
     pub fn number_of_interpolation_conditions(&mut self, value: usize) -> &mut Self {
-        assert!(value >= 4);
         self.number_of_interpolation_conditions = value;
         self
     }
 
-    pub fn initial_trust_region_radius(&mut self, value: f64) -> &mut Self {
-        assert!(value <= self.final_trust_region_radius);
-        self.initial_trust_region_radius = value;
+    pub fn initial_trust_region_radius(&mut self, value: impl Into<f64>) -> &mut Self {
+        self.initial_trust_region_radius = value.into();
         self
     }
 
-    pub fn final_trust_region_radius(&mut self, value: f64) -> &mut Self {
-        assert!(value >= self.initial_trust_region_radius);
-        self.final_trust_region_radius = value;
+    pub fn final_trust_region_radius(&mut self, value: impl Into<f64>) -> &mut Self {
+        self.final_trust_region_radius = value.into();
         self
     }
 
+    /// Sets the initial radius to a tenth of the largest magnitude of the starting point,
+    /// at least `0.1`, and the final radius to `relative_accuracy` times the initial one.
+    /// Starting points are divided by the scaling if one is set. A `relative_accuracy`
+    /// outside `(0, 1]` makes the run fail with an invalid radius or radius order.
+    pub fn auto_radii(&mut self, values: &[f64], relative_accuracy: f64) -> &mut Self {
+        let scaling = self.scaling.as_ref();
+        let scale = values.iter().enumerate()
+            .map(|(i, v)| v.abs()/scaling.and_then(|s| s.get(i)).map_or(1.0, |s| s.abs()))
+            .fold(1.0_f64, f64::max);
+        let initial_trust_region_radius = scale/10.0;
+        self.initial_trust_region_radius(initial_trust_region_radius)
+            .final_trust_region_radius(relative_accuracy*initial_trust_region_radius)
+    }
+
     pub fn max_function_calls_count(&mut self, value: usize) -> &mut Self {
         self.max_function_calls_count = value;
         self
     }
 
-    pub fn perform<F>(&mut self, values: &mut [f64], function: &F) -> f64
-            where F: Fn(&[f64]) -> f64 {
-        self.check(values);
-        self.resize_working_space();
-        let closure = Closure::new(function);
-        unsafe {
-            newuoa_closure_const(
-                &closure as *const _,
-                self.variables_count as i64,
-                self.number_of_interpolation_conditions as i64,
-                values.as_mut_ptr(),
-                self.initial_trust_region_radius,
-                self.final_trust_region_radius,
-                self.max_function_calls_count as i64,
-                self.working_space.as_mut_ptr(),
-            )
-        }
-    }
-
-    pub fn perform_mut<F>(&mut self, values: &mut [f64], function: &mut F) -> f64
+    /// Stops the run after the first evaluation that ends past this duration.
+    pub fn max_duration(&mut self, value: Duration) -> &mut Self {
+        self.options.max_duration = Some(value);
+        self
+    }
+
+    /// Stops the run at the first evaluation with a value not above `value`, or not below
+    /// it when maximizing.
+    pub fn stop_at_objective(&mut self, value: f64) -> &mut Self {
+        self.options.stop_at_objective = Some(value);
+        self
+    }
+
+    /// Stops the run when the best value improves by no more than `value` times its
+    /// magnitude.
+    pub fn ftol_rel(&mut self, value: f64) -> &mut Self {
+        self.options.ftol_rel = Some(value);
+        self
+    }
+
+    /// Stops the run when the best value improves by no more than `value`.
+    pub fn ftol_abs(&mut self, value: f64) -> &mut Self {
+        self.options.ftol_abs = Some(value);
+        self
+    }
+
+    pub fn non_finite_policy(&mut self, value: NonFinitePolicy) -> &mut Self {
+        self.options.non_finite_policy = value;
+        self
+    }
+
+    /// Keeps every evaluation in `OptimizationResult::history`.
+    pub fn record_history(&mut self, value: bool) -> &mut Self {
+        self.options.record_history = value;
+        self
+    }
+
+    pub fn verbosity(&mut self, value: Verbosity) -> &mut Self {
+        self.options.verbosity = value;
+        self
+    }
+
+    /// Writes the `verbosity` reports to `value` instead of stderr. Like the observer it is
+    /// not part of the configuration and not cloned.
+    pub fn diagnostics<W>(&mut self, value: W) -> &mut Self where W: Write + Send + 'static {
+        self.diagnostics = Some(Box::new(value));
+        self
+    }
+
+    /// Maximizes the objective by minimizing its negation. Results, history, observers,
+    /// reports and stopping criteria all keep the sign of the objective.
+    pub fn maximize(&mut self, value: bool) -> &mut Self {
+        self.maximize = value;
+        self
+    }
+
+    /// Typical magnitudes of the variables: the radii apply to `x[i]/value[i]` so that
+    /// variables of very different magnitudes move at comparable rates.
+    pub fn scaling(&mut self, value: &[f64]) -> &mut Self {
+        self.scaling = Some(value.to_vec());
+        self
+    }
+
+    /// Holds the variable at `value` and optimizes over the remaining ones, which must
+    /// still be at least two. The number of interpolation conditions applies to those.
+    pub fn fix_variable(&mut self, index: usize, value: f64) -> &mut Self {
+        match self.fixed_variables.binary_search_by_key(&index, |v| v.0) {
+            Ok(position) => self.fixed_variables[position].1 = value,
+            Err(position) => self.fixed_variables.insert(position, (index, value)),
+        }
+        self
+    }
+
+    pub fn unfix_variables(&mut self) -> &mut Self {
+        self.fixed_variables.clear();
+        self
+    }
+
+    /// Saves a `Checkpoint` of the incumbent to `path` after every `function_calls_count`
+    /// evaluations, to be continued with `resume_from` after a crash. A path ending with
+    /// `.zst` is zstd compressed, which needs the `zstd` feature.
+    pub fn checkpoint_every<P>(&mut self, function_calls_count: usize, path: P) -> &mut Self
+            where P: Into<PathBuf> {
+        self.checkpoint = Some((function_calls_count, path.into()));
+        self
+    }
+
+    /// Solver and start point continuing the run saved by `checkpoint_every`, with every
+    /// option of the saved run and the budget reduced by the function calls already made.
+    /// Checkpoints of the continued run count calls from zero against that reduced budget.
+    pub fn resume_from<P>(path: P) -> Result<(Newuoa, Vec<f64>), CheckpointError>
+            where P: AsRef<Path> {
+        let checkpoint = Checkpoint::load(path)?;
+        let mut solver = checkpoint.solver();
+        let remaining = checkpoint.configuration.settings.max_function_calls_count
+            .saturating_sub(checkpoint.function_calls_count);
+        solver.max_function_calls_count(remaining.max(1));
+        Ok((solver, checkpoint.point))
+    }
+
+    /// Adds a regularization term to the objective; the returned value includes it.
+    pub fn prior(&mut self, value: Prior) -> &mut Self {
+        self.prior = Some(value);
+        self
+    }
+
+    /// Called after every objective evaluation of `perform` and `perform_mut`. Returning
+    /// `ControlFlow::Break(())` stops the run with the best point found so far.
+    pub fn observer<O, R>(&mut self, mut value: O) -> &mut Self
+            where O: FnMut(&IterationInfo) -> R + Send + 'static, R: IntoControlFlow {
+        self.observer = Some(Box::new(move |info: &IterationInfo| value(info).into_control_flow()));
+        self
+    }
+
+    pub fn settings(&self) -> Settings {
+        Settings {
+            variables_count: self.variables_count,
+            number_of_interpolation_conditions: self.number_of_interpolation_conditions,
+            initial_trust_region_radius: self.initial_trust_region_radius,
+            final_trust_region_radius: self.final_trust_region_radius,
+            max_function_calls_count: self.max_function_calls_count,
+        }
+    }
+
+    /// Metadata of the latest `perform` or `perform_mut` call.
+    pub fn last_run(&self) -> Option<&RunMetadata> {
+        self.last_run.as_ref()
+    }
+
+    /// Observer sending every evaluation to `sender`, e.g. for a GUI thread plotting the
+    /// convergence of a run on a worker thread. The run goes on once the receiver is gone.
+    pub fn progress_sender(&mut self, sender: Sender<IterationUpdate>) -> &mut Self {
+        self.observer(move |info: &IterationInfo| {
+            let _ = sender.send(info.to_update());
+        })
+    }
+
+    /// Every option except the observer, for storing and reproducing experiments.
+    pub fn configuration(&self) -> Configuration {
+        Configuration {
+            settings: self.settings(),
+            max_duration: self.options.max_duration,
+            stop_at_objective: self.options.stop_at_objective,
+            ftol_rel: self.options.ftol_rel,
+            ftol_abs: self.options.ftol_abs,
+            non_finite_policy: self.options.non_finite_policy,
+            record_history: self.options.record_history,
+            verbosity: self.options.verbosity,
+            maximize: self.maximize,
+            scaling: self.scaling.clone(),
+            fixed_variables: self.fixed_variables.clone(),
+            checkpoint: self.checkpoint.clone(),
+            prior: self.prior.clone(),
+        }
+    }
+
+    /// Uses `value` as the working space, keeping its allocation if it is large enough.
+    pub fn working_space(&mut self, value: Vec<f64>) -> &mut Self {
+        self.working_space = value;
+        self
+    }
+
+    /// Leaves the solver without a working space until the next run allocates one.
+    pub fn take_working_space(&mut self) -> Vec<f64> {
+        std::mem::take(&mut self.working_space)
+    }
+
+    pub fn has_observer(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    /// Suspicious but valid settings, checked against the current configuration.
+    pub fn warnings(&self) -> Vec<Warning> {
+        Warning::check(&self.settings())
+    }
+
+    /// Bytes held by a configured solver during a run: the native working space plus the
+    /// solver itself and its options, and at most a full budget of recorded evaluations.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+        let working_space_size = Newuoa::required_workspace_len(
+            self.free_variables_count(),
+            self.number_of_interpolation_conditions);
+        let prior_size = self.prior.as_ref().map_or(0, |v| 2*v.len());
+        let scaling_size = self.scaling.as_ref().map_or(0, |v| 2*v.len());
+        let history_size = if self.options.record_history {
+            self.max_function_calls_count*(size_of::<Evaluation>()
+                + size_of::<f64>()*self.variables_count)
+        } else {
+            0
+        };
+        size_of::<Newuoa>() + size_of::<f64>()*(working_space_size + prior_size + scaling_size) + history_size
+    }
+
+    /// Starts a run evaluated step by step through `AskTell`.
+    pub fn ask_tell(&self, values: &[f64]) -> Result<AskTell, NewuoaError> {
+        self.validate(values)?;
+        Ok(AskTell::new(self.detached(), values.to_vec()))
+    }
+
+    pub fn iterates<F>(&self, values: &[f64], function: F) -> Result<Iterates<F>, NewuoaError>
             where F: FnMut(&[f64]) -> f64 {
-        self.check(values);
+        let ask_tell = self.ask_tell(values)?;
+        Ok(Iterates::new(ask_tell, function, self.initial_trust_region_radius, self.maximize))
+    }
+
+    #[cfg(feature = "async")]
+    pub fn stream<F>(&self, values: &[f64], function: F) -> Result<IterateStream, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 + Send + 'static {
+        self.iterates(values, function).map(IterateStream::new)
+    }
+
+    pub fn perform<F>(&mut self, values: &mut [f64], function: &F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: ?Sized + Fn(&[f64]) -> f64 {
+        self.perform_mut(values, &mut |x: &[f64]| function(x))
+    }
+
+    /// `function` may be a trait object, e.g. `&mut dyn FnMut(&[f64]) -> f64`, compiling the
+    /// run once for every objective of that type.
+    pub fn perform_mut<F>(&mut self, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: ?Sized + FnMut(&[f64]) -> f64 {
+        self.validate(values)?;
         self.resize_working_space();
-        let mut closure = ClosureMut::new(function);
-        unsafe {
-            newuoa_closure(
-                &mut closure as *mut _,
-                self.variables_count as i64,
-                self.number_of_interpolation_conditions as i64,
-                values.as_mut_ptr(),
-                self.initial_trust_region_radius,
-                self.final_trust_region_radius,
-                self.max_function_calls_count as i64,
-                self.working_space.as_mut_ptr(),
-            )
+        let mut working_space = std::mem::take(&mut self.working_space);
+        let result = self.perform_with_workspace(&mut working_space, values, function);
+        self.working_space = working_space;
+        result
+    }
+
+    /// `perform_mut` using `working_space` instead of the solver's own, which is left
+    /// untouched, e.g. a static buffer of `required_workspace_len` values.
+    pub fn perform_with_workspace<F>(&mut self, working_space: &mut [f64], values: &mut [f64],
+            function: &mut F) -> Result<OptimizationResult, NewuoaError>
+            where F: ?Sized + FnMut(&[f64]) -> f64 {
+        self.perform_stoppable(working_space, values, &mut |x: &[f64]| ControlFlow::Continue(function(x)))
+    }
+
+    /// `perform_with_workspace` for an objective that can stop the run by returning
+    /// `ControlFlow::Break`, which ends it with `TerminationReason::ObjectiveFailed`.
+    fn perform_stoppable<F>(&mut self, working_space: &mut [f64], values: &mut [f64],
+            function: &mut F) -> Result<OptimizationResult, NewuoaError>
+            where F: ?Sized + FnMut(&[f64]) -> ControlFlow<(), f64> {
+        self.validate(values)?;
+        let required_len = Newuoa::required_workspace_len(self.free_variables_count(),
+            self.number_of_interpolation_conditions);
+        if working_space.len() < required_len {
+            return Err(NewuoaError::WorkspaceTooShort {
+                workspace_len: working_space.len(),
+                required_len,
+            });
         }
+        let mut metadata = RunMetadata::new(self.settings());
+        #[cfg(feature = "log")]
+        log::info!("newuoa run {}: started with {:?}", metadata.id, metadata.settings);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("newuoa", run = %metadata.id,
+            n = self.variables_count, npt = self.number_of_interpolation_conditions,
+            budget = self.max_function_calls_count).entered();
+        let space = Space {
+            variables_count: self.variables_count,
+            scaling: self.scaling.as_ref().map(|v| &v[..]),
+            fixed_variables: &self.fixed_variables,
+        };
+        let mut internal = space.to_internal(values);
+        let configuration = self.checkpoint.as_ref().map(|_| self.configuration());
+        let mut run = Run::new(
+            metadata.settings.clone(),
+            self.options,
+            metadata.id,
+            space,
+            self.prior.as_ref(),
+            self.observer.as_mut(),
+            self.checkpoint.as_ref().zip(configuration).map(|(v, c)| (v.0, v.1.as_path(), c)));
+        run.diagnostics = self.diagnostics.as_mut();
+        let sign = if self.maximize { -1.0 } else { 1.0 };
+        run.sign = sign;
+        let stopped = Cell::new(false);
+        let mut instrumented = Instrumented::new(|v: &[f64]| match function(v) {
+            ControlFlow::Continue(value) => value,
+            ControlFlow::Break(()) => {
+                stopped.set(true);
+                f64::NAN
+            }
+        });
+        let value = {
+            let run = &mut run;
+            let mut function = |x: &[f64]| run.evaluate(&mut |v: &[f64]| {
+                let value = instrumented.call(v);
+                if stopped.get() { ControlFlow::Break(()) } else { ControlFlow::Continue(value) }
+            }, x);
+            let mut closure = ClosureMut::new(&mut function);
+            unsafe {
+                newuoa_closure(
+                    &mut closure as *mut _,
+                    internal.len() as i64,
+                    self.number_of_interpolation_conditions as i64,
+                    internal.as_mut_ptr(),
+                    self.initial_trust_region_radius,
+                    self.final_trust_region_radius,
+                    self.max_function_calls_count as i64,
+                    working_space.as_mut_ptr(),
+                )
+            }
+        };
+        metadata.finish();
+        if let Some(payload) = run.panic {
+            resume_unwind(payload);
+        }
+        let progress = run.progress;
+        let termination_reason = run.stop.unwrap_or(
+            if progress.function_calls_count >= self.max_function_calls_count {
+                TerminationReason::MaxFunctionCallsReached
+            } else {
+                TerminationReason::Converged
+            });
+        // The native routine may leave its last trial point in `internal`, report the
+        // incumbent tracked by the wrapper instead.
+        let value = if progress.best_point.is_empty() {
+            let mut point = Vec::new();
+            run.space.to_external(&internal, &mut point);
+            values[..self.variables_count].copy_from_slice(&point);
+            value
+        } else {
+            values[..self.variables_count].copy_from_slice(&progress.best_point);
+            progress.best_value
+        };
+        let final_trust_region_radius = match termination_reason {
+            TerminationReason::Converged => self.final_trust_region_radius,
+            _ => progress.rho.max(self.final_trust_region_radius),
+        };
+        #[cfg(feature = "log")]
+        log::info!("newuoa run {}: {:?} after {} evals, best f = {}, rho = {}", metadata.id,
+            termination_reason, progress.function_calls_count, sign*value, final_trust_region_radius);
+        #[cfg(feature = "tracing")]
+        tracing::info!(reason = ?termination_reason, evals = progress.function_calls_count,
+            best_f = sign*value, rho = final_trust_region_radius, "newuoa finished");
+        if self.options.verbosity >= Verbosity::Summary {
+            report(&mut run.diagnostics, format_args!("newuoa: {:?} after {} evals, best f = {}, rho = {}",
+                termination_reason, progress.function_calls_count, sign*value,
+                final_trust_region_radius));
+        }
+        self.last_run = Some(metadata.clone());
+        Ok(OptimizationResult {
+            value: sign*value,
+            solution: values[..self.variables_count].to_vec(),
+            function_calls_count: progress.function_calls_count,
+            final_trust_region_radius,
+            termination_reason,
+            metadata,
+            history: run.history.unwrap_or_default().into_iter()
+                .map(|v| Evaluation {value: sign*v.value, ..v})
+                .collect(),
+            rho_history: run.rho_history.unwrap_or_default().into_iter()
+                .map(|v| RadiusChange {best_value: sign*v.best_value, ..v})
+                .collect(),
+            checkpoint_error: run.checkpoint_error,
+            cache_hits: 0,
+            objective_duration: instrumented.total_duration(),
+        })
     }
 
-    fn check(&self, values: &[f64]) {
-        assert!(values.len() >= self.variables_count);
-        assert!(self.number_of_interpolation_conditions >= self.variables_count + 2);
-        assert!(self.number_of_interpolation_conditions <=
-            (self.variables_count + 1)*(self.variables_count + 2)/2);
+    /// Re-optimizes after a small change of the problem, starting from the previous solution
+    /// with an initial radius of ten times the one the previous run ended at, within the
+    /// configured radii. The native routine cannot take an interpolation set, so it
+    /// rebuilds its model from `npt` evaluations around the previous solution.
+    pub fn resume<F>(&mut self, previous: &OptimizationResult, function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: ?Sized + FnMut(&[f64]) -> f64 {
+        let initial_trust_region_radius = self.initial_trust_region_radius;
+        let radius = (10.0*previous.final_trust_region_radius)
+            .min(initial_trust_region_radius)
+            .max(self.final_trust_region_radius);
+        self.initial_trust_region_radius = radius;
+        let result = self.perform_mut(&mut previous.solution.clone(), function);
+        self.initial_trust_region_radius = initial_trust_region_radius;
+        result
     }
 
-    fn resize_working_space(&mut self) {
-        use std::iter::repeat;
-        let working_space_size = Newuoa::working_space_size(
-            self.number_of_interpolation_conditions,
-            self.variables_count);
-        if self.working_space.len() != working_space_size {
-            self.working_space = repeat(0.0).take(working_space_size).collect::<_>();
+    /// Every setting is checked here rather than by the setters, so invalid ones fail the
+    /// run instead of panicking.
+    fn validate(&self, values: &[f64]) -> Result<(), NewuoaError> {
+        let n = self.variables_count;
+        if let Some(&(index, _)) = self.fixed_variables.last() {
+            if index >= n {
+                return Err(NewuoaError::FixedVariableOutOfRange {index, variables_count: n});
+            }
+        }
+        let free = self.free_variables_count();
+        if free < 2 {
+            return Err(NewuoaError::DimensionTooSmall {variables_count: free});
         }
+        if values.len() < n {
+            return Err(NewuoaError::ValuesTooShort {values_count: values.len(), variables_count: n});
+        }
+        if let Some(ref scaling) = self.scaling {
+            if scaling.len() != n {
+                return Err(NewuoaError::ScalingDimensionMismatch {
+                    scaling_len: scaling.len(),
+                    variables_count: n,
+                });
+            }
+            if let Some((index, &factor)) = scaling.iter().enumerate().find(|v| !(*v.1 > 0.0 && v.1.is_finite())) {
+                return Err(NewuoaError::InvalidScaling {index, factor});
+            }
+        }
+        if let Some(ref prior) = self.prior {
+            if prior.len() != n {
+                return Err(NewuoaError::PriorDimensionMismatch {
+                    prior_len: prior.len(),
+                    variables_count: n,
+                });
+            }
+        }
+        let npt = self.number_of_interpolation_conditions;
+        let (min, max) = (free + 2, (free + 1)*(free + 2)/2);
+        if npt < min || npt > max {
+            return Err(NewuoaError::InvalidInterpolationConditions {
+                number_of_interpolation_conditions: npt,
+                min,
+                max,
+            });
+        }
+        Radius::try_from(self.initial_trust_region_radius)?;
+        Radius::try_from(self.final_trust_region_radius)?;
+        Budget::try_from(self.max_function_calls_count)?;
+        for &tolerance in self.options.ftol_rel.iter().chain(self.options.ftol_abs.iter()) {
+            if tolerance < 0.0 || tolerance.is_nan() {
+                return Err(NewuoaError::InvalidTolerance {tolerance});
+            }
+        }
+        if self.checkpoint.as_ref().is_some_and(|v| v.0 == 0) {
+            return Err(NewuoaError::InvalidCheckpointPeriod);
+        }
+        if let NonFinitePolicy::Penalty(penalty) = self.options.non_finite_policy {
+            if !penalty.is_finite() {
+                return Err(NewuoaError::NonFinitePenalty {penalty});
+            }
+        }
+        if self.initial_trust_region_radius < self.final_trust_region_radius {
+            return Err(NewuoaError::RadiusOrderInvalid {
+                initial_trust_region_radius: self.initial_trust_region_radius,
+                final_trust_region_radius: self.final_trust_region_radius,
+            });
+        }
+        Ok(())
+    }
+
+    /// Copy of the options without the observer and the working space.
+    fn detached(&self) -> Newuoa {
+        Newuoa {
+            variables_count: self.variables_count,
+            number_of_interpolation_conditions: self.number_of_interpolation_conditions,
+            initial_trust_region_radius: self.initial_trust_region_radius,
+            final_trust_region_radius: self.final_trust_region_radius,
+            max_function_calls_count: self.max_function_calls_count,
+            options: self.options,
+            maximize: self.maximize,
+            scaling: self.scaling.clone(),
+            fixed_variables: self.fixed_variables.clone(),
+            checkpoint: self.checkpoint.clone(),
+            prior: self.prior.clone(),
+            observer: None,
+            diagnostics: None,
+            last_run: None,
+            working_space: Vec::new(),
+        }
+    }
+
+    fn free_variables_count(&self) -> usize {
+        self.variables_count.saturating_sub(self.fixed_variables.len())
+    }
+
+    fn resize_working_space(&mut self) {
+        let working_space_size = Newuoa::required_workspace_len(
+            self.free_variables_count(),
+            self.number_of_interpolation_conditions);
+        self.working_space.resize(working_space_size, 0.0);
     }
 
-    fn working_space_size(number_of_interpolation_conditions: usize, variables_count: usize) -> usize {
-        3*variables_count*(variables_count + 3)/2
-        + (number_of_interpolation_conditions + 13)
-            *(number_of_interpolation_conditions + variables_count)
+    /// Length of the working space the native routine needs for `variables_count` free
+    /// variables: `(npt + 13)(npt + n) + 3n(n + 3)/2`.
+    pub const fn required_workspace_len(variables_count: usize,
+            number_of_interpolation_conditions: usize) -> usize {
+        (number_of_interpolation_conditions + 13)*(number_of_interpolation_conditions + variables_count)
+        + 3*variables_count*(variables_count + 3)/2
     }
 }
 
+#[test]
+fn test_working_space_should_match_newuoa_requirement() {
+    let mut newuoa = Newuoa::new();
+    assert_eq!(newuoa.working_space.len(), (4 + 13)*(4 + 2) + 3*2*(2 + 3)/2);
+    newuoa.variables_count(5).number_of_interpolation_conditions(8).resize_working_space();
+    assert_eq!(newuoa.working_space.len(), (8 + 13)*(8 + 5) + 3*5*(5 + 3)/2);
+    let capacity = newuoa.working_space.capacity();
+    newuoa.variables_count(2).number_of_interpolation_conditions(4).resize_working_space();
+    assert_eq!(newuoa.working_space.len(), 117);
+    assert_eq!(newuoa.working_space.capacity(), capacity);
+}
+
+#[test]
+fn test_for_dimension_should_size_working_space() {
+    let newuoa = Newuoa::for_dimension(10);
+    assert_eq!(newuoa.settings().number_of_interpolation_conditions, 21);
+    assert_eq!(newuoa.settings().max_function_calls_count, 5000);
+    assert_eq!(newuoa.working_space.len(), Newuoa::required_workspace_len(10, 21));
+}
+
+#[test]
+fn test_auto_radii_should_scale_with_initial_point() {
+    let mut newuoa = Newuoa::new();
+    newuoa.auto_radii(&[0.5, -30.0], 0.5);
+    assert_eq!(newuoa.settings().initial_trust_region_radius, 3.0);
+    assert_eq!(newuoa.settings().final_trust_region_radius, 1.5);
+    newuoa.auto_radii(&[0.0, 0.5], 1e-6);
+    assert_eq!(newuoa.settings().initial_trust_region_radius, 0.1);
+}
+
+#[test]
+fn test_clone_should_keep_configuration_without_observer() {
+    let mut newuoa = Newuoa::for_dimension(3);
+    newuoa.maximize(true).observer(|_: &IterationInfo| ());
+    let clone = newuoa.clone();
+    assert_eq!(clone.configuration(), newuoa.configuration());
+    assert!(newuoa.has_observer());
+    assert!(!clone.has_observer());
+    assert!(format!("{:?}", clone).contains("maximize: true"));
+}
+
+#[test]
+fn test_perform_mut_should_run_concurrently_on_separate_solvers() {
+    use std::thread;
+    fn assert_send<T: Send>(_: &T) {}
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(200).observer(|_: &IterationInfo| ());
+    assert_send(&solver);
+    let expected = solver.clone().perform_mut(&mut [1.0, 2.0], &mut |x: &[f64]| {
+        (x[0] - 1.0).powi(2) + (x[1] + 0.5).powi(2)
+    }).unwrap();
+    let handles = (0..8)
+        .map(|_| {
+            let mut solver = solver.clone();
+            thread::spawn(move || {
+                solver.perform_mut(&mut [1.0, 2.0], &mut |x: &[f64]| {
+                    (x[0] - 1.0).powi(2) + (x[1] + 0.5).powi(2)
+                }).unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        let result = handle.join().unwrap();
+        assert_eq!(result.solution, expected.solution);
+        assert_eq!(result.function_calls_count, expected.function_calls_count);
+    }
+}
+
+#[test]
+fn test_perform_with_workspace_should_check_length() {
+    let mut working_space = [0.0; 200];
+    let mut newuoa = Newuoa::new();
+    newuoa.number_of_interpolation_conditions(5).max_function_calls_count(20);
+    let required_len = Newuoa::required_workspace_len(2, 5);
+    let result = newuoa.perform_with_workspace(&mut working_space[..required_len - 1],
+        &mut [1.0, 1.0], &mut |x: &[f64]| x[0]*x[0] + x[1]*x[1]);
+    assert_eq!(result, Err(NewuoaError::WorkspaceTooShort {workspace_len: required_len - 1, required_len}));
+    let result = newuoa.perform_with_workspace(&mut working_space, &mut [1.0, 1.0],
+        &mut |x: &[f64]| x[0]*x[0] + x[1]*x[1]).unwrap();
+    assert_eq!(result.function_calls_count, 20);
+}
+
+#[test]
+fn test_perform_mut_should_accept_trait_objects() {
+    let mut sphere = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    let function: &mut dyn FnMut(&[f64]) -> f64 = &mut sphere;
+    let result = Newuoa::new()
+        .max_function_calls_count(20)
+        .perform_mut(&mut [1.0, 1.0], function)
+        .unwrap();
+    assert_eq!(result.function_calls_count, 20);
+}
+
+#[test]
+fn test_estimated_memory_bytes_should_include_working_space() {
+    let mut newuoa = Newuoa::new();
+    newuoa.variables_count(10).number_of_interpolation_conditions(21);
+    assert!(newuoa.estimated_memory_bytes() >= 8*((21 + 13)*(21 + 10) + 3*10*13/2));
+}
+
+#[test]
+fn test_perform_should_reject_invalid_settings() {
+    let function = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    assert_eq!(Newuoa::new().number_of_interpolation_conditions(3).perform(&mut [1.0, 1.0], &function),
+        Err(NewuoaError::InvalidInterpolationConditions {number_of_interpolation_conditions: 3, min: 4, max: 6}));
+    assert_eq!(Newuoa::new().initial_trust_region_radius(0.0).perform(&mut [1.0, 1.0], &function),
+        Err(NewuoaError::InvalidTrustRegionRadius {radius: 0.0}));
+    assert_eq!(Newuoa::new().max_function_calls_count(0).perform(&mut [1.0, 1.0], &function),
+        Err(NewuoaError::InvalidBudget {max_function_calls_count: 0}));
+    assert_eq!(Newuoa::new().ftol_abs(-1.0).perform(&mut [1.0, 1.0], &function),
+        Err(NewuoaError::InvalidTolerance {tolerance: -1.0}));
+    assert_eq!(Newuoa::new().scaling(&[1.0, 0.0]).perform(&mut [1.0, 1.0], &function),
+        Err(NewuoaError::InvalidScaling {index: 1, factor: 0.0}));
+    assert_eq!(Newuoa::new().checkpoint_every(0, "unused").perform(&mut [1.0, 1.0], &function),
+        Err(NewuoaError::InvalidCheckpointPeriod));
+}
+
+#[test]
+fn test_perform_should_reject_swapped_radii() {
+    let result = Newuoa::new()
+        .initial_trust_region_radius(1e-3)
+        .final_trust_region_radius(1e3)
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]);
+    assert_eq!(result, Err(NewuoaError::RadiusOrderInvalid {
+        initial_trust_region_radius: 1e-3,
+        final_trust_region_radius: 1e3,
+    }));
+}
+
+#[test]
+fn test_observer_should_be_called_after_every_evaluation() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let calls_count = Arc::new(AtomicUsize::new(0));
+    let observed = calls_count.clone();
+    let result = Newuoa::new()
+        .max_function_calls_count(20)
+        .observer(move |info: &IterationInfo| {
+            assert!(info.best_value <= info.value);
+            observed.fetch_add(1, Ordering::SeqCst);
+        })
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(calls_count.load(Ordering::SeqCst), result.function_calls_count);
+}
+
+#[test]
+fn test_observer_should_stop_run() {
+    let mut calls_count = 0;
+    let result = Newuoa::new()
+        .max_function_calls_count(100)
+        .observer(|info: &IterationInfo| {
+            if info.function_calls_count >= 3 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        })
+        .perform_mut(&mut [1.0, 1.0], &mut |x: &[f64]| {
+            calls_count += 1;
+            x[0]*x[0] + x[1]*x[1]
+        })
+        .unwrap();
+    assert_eq!(calls_count, 3);
+    assert_eq!(result.function_calls_count, 3);
+    assert_eq!(result.termination_reason, TerminationReason::StoppedByUser);
+}
+
+#[test]
+fn test_max_duration_should_stop_run() {
+    let result = Newuoa::new()
+        .max_duration(Duration::from_millis(0))
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(result.function_calls_count, 1);
+    assert_eq!(result.termination_reason, TerminationReason::TimeLimit);
+}
+
+#[test]
+fn test_stop_at_objective_should_stop_run() {
+    let result = Newuoa::new()
+        .stop_at_objective(1.0)
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert!(result.value <= 1.0);
+    assert_eq!(result.termination_reason, TerminationReason::TargetReached);
+}
+
+#[test]
+fn test_ftol_abs_should_stop_run_on_first_improvement() {
+    let result = Newuoa::new()
+        .ftol_abs(1e3)
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert!(result.value < 2.0);
+    assert_eq!(result.termination_reason, TerminationReason::FunctionToleranceReached);
+}
+
+#[test]
+fn test_verbosity_should_be_kept_in_configuration() {
+    let mut newuoa = Newuoa::new();
+    newuoa.max_function_calls_count(5).verbosity(Verbosity::PerEval);
+    assert_eq!(Newuoa::from_configuration(&newuoa.configuration()).options.verbosity, Verbosity::PerEval);
+    let result = newuoa.perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]).unwrap();
+    assert_eq!(result.function_calls_count, 5);
+}
+
+#[test]
+fn test_diagnostics_should_receive_verbosity_reports() {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+    impl Write for Buffer {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(data)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    Newuoa::new()
+        .max_function_calls_count(5)
+        .verbosity(Verbosity::PerEval)
+        .diagnostics(Buffer(buffer.clone()))
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+    assert_eq!(output.lines().count(), 6);
+    assert!(output.starts_with("newuoa: eval 1: f = 2, best f = 2, rho = 1\n"));
+}
+
+#[test]
+fn test_progress_sender_should_send_every_evaluation() {
+    use std::sync::mpsc::channel;
+    use std::thread;
+    let (sender, receiver) = channel();
+    let worker = thread::spawn(move || {
+        Newuoa::new()
+            .max_function_calls_count(10)
+            .progress_sender(sender)
+            .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+            .unwrap()
+    });
+    let updates = receiver.iter().collect::<Vec<_>>();
+    let result = worker.join().unwrap();
+    assert_eq!(updates.len(), 10);
+    assert_eq!(updates.last().unwrap().best_value, result.value);
+}
+
+#[test]
+fn test_record_history_should_keep_every_evaluation() {
+    let result = Newuoa::new()
+        .max_function_calls_count(10)
+        .record_history(true)
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(result.history.len(), result.function_calls_count);
+    assert_eq!(result.history[0], Evaluation {point: vec![1.0, 1.0], value: 2.0});
+    assert_eq!(result.rho_history[0], RadiusChange {function_calls_count: 1, rho: 1.0, best_value: 2.0});
+    assert!(result.rho_history.windows(2).all(|v| v[1].rho < v[0].rho
+        && v[1].function_calls_count > v[0].function_calls_count));
+}
+
+#[test]
+fn test_last_run_should_match_latest_result_metadata() {
+    let mut newuoa = Newuoa::new();
+    assert!(newuoa.last_run().is_none());
+    newuoa.max_function_calls_count(20);
+    let first = newuoa.perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]).unwrap();
+    assert_eq!(newuoa.last_run(), Some(&first.metadata));
+    let second = newuoa.perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]).unwrap();
+    assert_ne!(first.metadata.id, second.metadata.id);
+    assert_eq!(newuoa.last_run(), Some(&second.metadata));
+    assert!(newuoa.clone().last_run().is_none());
+}
+
+#[test]
+fn test_perform_should_return_best_evaluated_point() {
+    let mut values = [1.0, 1.0];
+    let result = Newuoa::new()
+        .max_function_calls_count(7)
+        .record_history(true)
+        .perform(&mut values, &|x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    let best = result.history.iter().min_by(|a, b| a.value.partial_cmp(&b.value).unwrap()).unwrap();
+    assert_eq!(result.value, best.value);
+    assert_eq!(result.solution, best.point);
+    assert_eq!(&values[..], &best.point[..]);
+}
+
+#[test]
+fn test_non_finite_policy_abort_should_stop_run() {
+    let mut values = [1.0, 1.0];
+    let result = Newuoa::new()
+        .non_finite_policy(NonFinitePolicy::Abort)
+        .perform(&mut values, &|x: &[f64]| if x[0] < 1.0 { f64::NAN } else { x[0]*x[0] + x[1]*x[1] })
+        .unwrap();
+    assert_eq!(result.termination_reason, TerminationReason::NonFiniteObjective);
+    assert_eq!(result.value, 2.0);
+    assert_eq!(result.solution, vec![1.0, 1.0]);
+}
+
+#[test]
+fn test_non_finite_values_should_be_replaced_by_finite_penalty() {
+    use std::sync::mpsc::channel;
+    let mut values = [1.5, 1.5];
+    let (sender, receiver) = channel();
+    let result = Newuoa::new()
+        .max_function_calls_count(500)
+        .progress_sender(sender)
+        .perform(&mut values, &|x: &[f64]| {
+            if x[0] > 2.0 { f64::NAN } else { (x[0] - 1.0).powi(2) + (x[1] - 1.0).powi(2) }
+        })
+        .unwrap();
+    assert_eq!(result.termination_reason, TerminationReason::Converged);
+    assert!(result.value < 1e-6);
+    assert!(receiver.try_iter().all(|v| v.value.is_finite() && v.value < 1e3));
+}
+
+#[test]
+fn test_perform_should_resume_objective_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    let mut solver = Newuoa::new();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        solver.perform(&mut [1.0, 1.0], &|x: &[f64]| if x[0] < 1.0 { panic!("objective") } else { x[0] })
+    }));
+    assert_eq!(result.unwrap_err().downcast_ref::<&str>(), Some(&"objective"));
+    assert!(solver.perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]).is_ok());
+}
+
+#[test]
+fn test_maximize_should_report_objective_sign() {
+    let result = Newuoa::new()
+        .maximize(true)
+        .max_function_calls_count(30)
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| -x[0]*x[0] - x[1]*x[1])
+        .unwrap();
+    assert!(result.value > -2.0 && result.value <= 0.0);
+}
+
+#[test]
+fn test_maximize_should_keep_objective_sign_in_observers_and_stopping_criteria() {
+    use std::sync::mpsc::channel;
+    let (sender, receiver) = channel();
+    let result = Newuoa::new()
+        .maximize(true)
+        .stop_at_objective(-0.5)
+        .max_function_calls_count(100)
+        .progress_sender(sender)
+        .perform(&mut [1.0, 1.0], &|x: &[f64]| -x[0]*x[0] - x[1]*x[1])
+        .unwrap();
+    assert_eq!(result.termination_reason, TerminationReason::TargetReached);
+    assert!(result.value >= -0.5);
+    let updates = receiver.try_iter().collect::<Vec<_>>();
+    assert_eq!(updates[0].value, -2.0);
+    assert!(updates.windows(2).all(|v| v[1].best_value >= v[0].best_value));
+    assert_eq!(updates.last().unwrap().best_value, result.value);
+}
+
+#[test]
+fn test_scaling_should_pass_unscaled_points() {
+    let mut values = [1e-5, 1e4];
+    let mut first = Vec::new();
+    let result = Newuoa::new()
+        .scaling(&[1e-5, 1e4])
+        .max_function_calls_count(50)
+        .perform_mut(&mut values, &mut |x: &[f64]| {
+            if first.is_empty() {
+                first = x.to_vec();
+            }
+            (x[0]/1e-5 - 2.0).powi(2) + (x[1]/1e4 - 2.0).powi(2)
+        })
+        .unwrap();
+    assert_eq!(first, vec![1e-5, 1e4]);
+    assert!(result.value < 2.0);
+    assert_eq!(result.solution, values.to_vec());
+}
+
+#[test]
+fn test_fix_variable_should_hold_value() {
+    let mut values = [1.0, 1.0, 1.0];
+    let result = Newuoa::new()
+        .variables_count(3)
+        .fix_variable(1, 3.0)
+        .max_function_calls_count(50)
+        .perform(&mut values, &|x: &[f64]| {
+            assert_eq!(x[1], 3.0);
+            x[0]*x[0] + x[1]*x[1] + x[2]*x[2]
+        })
+        .unwrap();
+    assert_eq!(values[1], 3.0);
+    assert!(result.value < 11.0);
+}
+
+#[test]
+fn test_resume_should_start_from_previous_solution() {
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(30).final_trust_region_radius(1e-3);
+    let previous = solver.perform(&mut [1.0, 1.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]).unwrap();
+    let mut first = Vec::new();
+    solver.resume(&previous, &mut |x: &[f64]| {
+        if first.is_empty() {
+            first = x.to_vec();
+        }
+        (x[0] - 0.1).powi(2) + x[1]*x[1]
+    }).unwrap();
+    assert_eq!(first, previous.solution);
+    assert_eq!(solver.settings().initial_trust_region_radius, 1.0);
+}
+
+#[test]
+fn test_resume_from_should_continue_checkpointed_run() {
+    use std::env::temp_dir;
+    use std::fs::remove_file;
+    let path = temp_dir().join(format!("newuoa-resume-{}.bin", std::process::id()));
+    let function = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    let result = Newuoa::new()
+        .max_function_calls_count(20)
+        .checkpoint_every(5, &path)
+        .perform(&mut [1.0, 1.0], &function)
+        .unwrap();
+    assert_eq!(result.checkpoint_error, None);
+    let (mut solver, mut values) = Newuoa::resume_from(&path).unwrap();
+    remove_file(&path).unwrap();
+    assert!(solver.settings().max_function_calls_count <= 15);
+    assert!(function(&values) <= 2.0);
+    assert!(solver.perform(&mut values, &function).is_ok());
+}
+
+#[test]
+fn test_resume_from_should_keep_maximize_and_fixed_variables() {
+    use std::env::temp_dir;
+    use std::fs::remove_file;
+    let path = temp_dir().join(format!("newuoa-resume-options-{}.bin", std::process::id()));
+    let function = |x: &[f64]| -x[0]*x[0] - x[1]*x[1] - x[2]*x[2];
+    let mut solver = Newuoa::new();
+    solver
+        .variables_count(3)
+        .maximize(true)
+        .fix_variable(1, 3.0)
+        .max_function_calls_count(20)
+        .checkpoint_every(5, &path);
+    let result = solver.perform(&mut [1.0, 3.0, 1.0], &function).unwrap();
+    assert_eq!(result.checkpoint_error, None);
+    let (mut resumed, mut values) = Newuoa::resume_from(&path).unwrap();
+    let expected = Configuration {
+        settings: resumed.settings(),
+        ..solver.configuration()
+    };
+    assert_eq!(resumed.configuration(), expected);
+    resumed.checkpoint = None;
+    remove_file(&path).unwrap();
+    assert_eq!(values[1], 3.0);
+    let result = resumed.perform(&mut values, &|x: &[f64]| {
+        assert_eq!(x[1], 3.0);
+        function(x)
+    }).unwrap();
+    assert!(result.value <= -9.0 && result.value > -11.0);
+}
+
+#[test]
+fn test_from_configuration_should_restore_options() {
+    let mut solver = Newuoa::new();
+    solver
+        .variables_count(3)
+        .ftol_abs(1e-4)
+        .maximize(true)
+        .scaling(&[1.0, 2.0, 3.0])
+        .fix_variable(2, 0.5)
+        .prior(Prior::gaussian(&[0.0, 0.0, 0.0], &[1.0, 1.0, 1.0]));
+    let configuration = solver.configuration();
+    assert_eq!(Newuoa::from_configuration(&configuration).configuration(), configuration);
+}
+
 #[test]
 fn test_pefrorm_mut_with_all_settings_should_succeed() {
-    let mut calls_count = Box::new(0);
     let mut values = [10.0, 10.0, 10.0];
-    let result = {
-        let mut function = |x: &[f64]| -> f64 {
+    let result = Newuoa::new()
+        .variables_count(values.len())
+        .number_of_interpolation_conditions((values.len() + 1)*(values.len() + 2)/2)
+        .initial_trust_region_radius(1e3)
+        .final_trust_region_radius(1e-3)
+        .max_function_calls_count(25)
+        .perform_mut(&mut values, &mut |x: &[f64]| -> f64 {
             assert_eq!(x.len(), 3);
-            *calls_count += 1;
             x[0]*x[0] + x[1]*x[1] + x[2]*x[2]
-        };
-        Newuoa::new()
-            .variables_count(values.len())
-            .number_of_interpolation_conditions((values.len() + 1)*(values.len() + 2)/2)
-            .initial_trust_region_radius(1e-3)
-            .final_trust_region_radius(1e3)
-            .max_function_calls_count(25)
-            .perform_mut(&mut values, &mut function)
-    };
+        })
+        .unwrap();
     for x in values.iter() {
         assert!(*x <= 1e-3);
     }
-    assert!(result <= 1e-3);
-    assert_eq!(*calls_count, 25);
+    assert!(result.value <= 1e-3);
+    assert_eq!(result.function_calls_count, 25);
+    assert_eq!(result.termination_reason, TerminationReason::MaxFunctionCallsReached);
 }
@@ -0,0 +1,112 @@
+pub fn gradient<F>(function: &F, x: &[f64], step: f64) -> Vec<f64> where F: Fn(&[f64]) -> f64 {
+    let mut point = x.to_vec();
+    (0..x.len())
+        .map(|i| {
+            let h = step*x[i].abs().max(1.0);
+            point[i] = x[i] + h;
+            let forward = function(&point);
+            point[i] = x[i] - h;
+            let backward = function(&point);
+            point[i] = x[i];
+            (forward - backward)/(2.0*h)
+        })
+        .collect()
+}
+
+pub fn hessian<F>(function: &F, x: &[f64], step: f64) -> Vec<Vec<f64>> where F: Fn(&[f64]) -> f64 {
+    let n = x.len();
+    let h = x.iter().map(|v| step*v.abs().max(1.0)).collect::<Vec<_>>();
+    let at = |shifts: &[(usize, f64)]| {
+        let mut point = x.to_vec();
+        for &(i, s) in shifts.iter() {
+            point[i] += s*h[i];
+        }
+        function(&point)
+    };
+    let center = function(x);
+    let mut result = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        result[i][i] = (at(&[(i, 1.0)]) - 2.0*center + at(&[(i, -1.0)]))/(h[i]*h[i]);
+        for j in 0..i {
+            let value = (at(&[(i, 1.0), (j, 1.0)]) - at(&[(i, 1.0), (j, -1.0)])
+                - at(&[(i, -1.0), (j, 1.0)]) + at(&[(i, -1.0), (j, -1.0)]))/(4.0*h[i]*h[j]);
+            result[i][j] = value;
+            result[j][i] = value;
+        }
+    }
+    result
+}
+
+pub fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut result = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect::<Vec<Vec<f64>>>();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&l, &r| a[l][column].abs().total_cmp(&a[r][column].abs()))?;
+        if a[pivot][column].abs() < 1e-300 {
+            return None;
+        }
+        a.swap(column, pivot);
+        result.swap(column, pivot);
+        let scale = a[column][column];
+        for j in 0..n {
+            a[column][j] /= scale;
+            result[column][j] /= scale;
+        }
+        for i in (0..n).filter(|&i| i != column) {
+            let factor = a[i][column];
+            for j in 0..n {
+                a[i][j] -= factor*a[column][j];
+                result[i][j] -= factor*result[column][j];
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Lower triangular factor `L` with `L*L^T = matrix`, `None` when the matrix is not
+/// positive definite.
+pub fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut result = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let value = matrix[i][j] - (0..j).map(|k| result[i][k]*result[j][k]).sum::<f64>();
+            if i == j && value > 0.0 {
+                result[i][i] = value.sqrt();
+            } else if i == j {
+                return None;
+            } else {
+                result[i][j] = value/result[j][j];
+            }
+        }
+    }
+    Some(result)
+}
+
+pub fn solve(matrix: &[Vec<f64>], rhs: &[f64]) -> Option<Vec<f64>> {
+    invert(matrix).map(|inverse| {
+        inverse.iter().map(|row| row.iter().zip(rhs.iter()).map(|(a, b)| a*b).sum()).collect()
+    })
+}
+
+#[test]
+fn test_hessian_inverse_of_quadratic_should_match_covariance() {
+    let function = |x: &[f64]| 2.0*x[0]*x[0] + x[0]*x[1] + x[1]*x[1];
+    let inverse = invert(&hessian(&function, &[0.5, -0.5], 1e-3)).unwrap();
+    let expected = [[2.0/7.0, -1.0/7.0], [-1.0/7.0, 4.0/7.0]];
+    for i in 0..2 {
+        for j in 0..2 {
+            assert!((inverse[i][j] - expected[i][j]).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_cholesky_should_factor_positive_definite_matrices_only() {
+    let factor = cholesky(&[vec![4.0, 2.0], vec![2.0, 5.0]]).unwrap();
+    assert_eq!(factor, vec![vec![2.0, 0.0], vec![1.0, 2.0]]);
+    assert_eq!(cholesky(&[vec![1.0, 2.0], vec![2.0, 1.0]]), None);
+    assert_eq!(cholesky(&[vec![-1.0, 0.0], vec![0.0, -1.0]]), None);
+}
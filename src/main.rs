@@ -1,25 +1,414 @@
 extern crate newuoa;
+#[cfg(feature = "cli")]
+extern crate meval;
+#[cfg(feature = "cli")]
+extern crate serde;
+#[cfg(feature = "cli")]
+extern crate serde_json;
+#[cfg(feature = "cli")]
+extern crate toml;
+
+use std::env;
+use std::process::exit;
+
+#[cfg(feature = "cli")]
+use serde::Deserialize;
+
+use newuoa::Newuoa;
+use newuoa::restarts::Restarts;
+use newuoa::result::OptimizationResult;
+use newuoa::transforms::Transform;
+
+const USAGE: &str = "usage: newuoa [--config PATH] (--expr EXPRESSION | --plugin PATH) --x0 X0,X1,... [OPTIONS]
+  --config PATH      TOML or, with a .json extension, JSON file with any of the fields
+                     expr, plugin, x0, dimension, rhobeg, rhoend, npt, budget, transforms
+                     and restarts, overridden by the command line
+  --expr EXPRESSION  objective of the variables x0, x1, ..., e.g. \"x0^2 + (x1-3)^2\"
+  --plugin PATH      shared library exporting `double eval(size_t n, const double *x)`
+  --x0 VALUES        comma separated starting point
+  --dimension VALUE  number of variables, starting from zeros without --x0
+  --rhobeg VALUE     initial trust region radius
+  --rhoend VALUE     final trust region radius
+  --npt VALUE        number of interpolation conditions
+  --budget VALUE     maximum number of function calls, across all runs with restarts
+  --restarts VALUE   maximum number of restarts from a perturbed solution
+Without an objective minimizes a built-in quadratic.
+
+# Safety
+
+--plugin runs the code of the library in this process. Its `eval` symbol is called with
+that exact signature whatever it actually is, a mismatch being undefined behaviour, and
+must not unwind. Only load libraries you trust.";
+
+type Function = Box<dyn Fn(&[f64]) -> f64>;
+
+/// Everything the command line or a config file can set.
+#[derive(Default)]
+#[cfg_attr(feature = "cli", derive(Deserialize))]
+#[cfg_attr(feature = "cli", serde(default, deny_unknown_fields))]
+struct Problem {
+    expr: Option<String>,
+    plugin: Option<String>,
+    x0: Option<Vec<f64>>,
+    dimension: Option<usize>,
+    rhobeg: Option<f64>,
+    rhoend: Option<f64>,
+    npt: Option<usize>,
+    budget: Option<usize>,
+    /// One per variable, `Transform::Identity` for all of them when empty.
+    transforms: Vec<Transform>,
+    restarts: Option<usize>,
+}
+
+impl Problem {
+    /// Fills the fields not set yet from `other`. An objective set here replaces both
+    /// objective fields of `other`, as they are exclusive.
+    fn merge(&mut self, other: Problem) {
+        if self.expr.is_none() && self.plugin.is_none() {
+            self.expr = other.expr;
+            self.plugin = other.plugin;
+        }
+        self.x0 = self.x0.take().or(other.x0);
+        self.dimension = self.dimension.or(other.dimension);
+        self.rhobeg = self.rhobeg.or(other.rhobeg);
+        self.rhoend = self.rhoend.or(other.rhoend);
+        self.npt = self.npt.or(other.npt);
+        self.budget = self.budget.or(other.budget);
+        if self.transforms.is_empty() {
+            self.transforms = other.transforms;
+        }
+        self.restarts = self.restarts.or(other.restarts);
+    }
+
+    fn solver(&self, variables_count: usize) -> Newuoa {
+        let mut result = Newuoa::for_dimension(variables_count);
+        if let Some(value) = self.rhobeg {
+            result.initial_trust_region_radius(value);
+        }
+        if let Some(value) = self.rhoend {
+            result.final_trust_region_radius(value);
+        }
+        if let Some(value) = self.npt {
+            result.number_of_interpolation_conditions(value);
+        }
+        if let Some(value) = self.budget {
+            result.max_function_calls_count(value);
+        }
+        result
+    }
+
+    fn start(&self) -> Vec<f64> {
+        match (&self.x0, self.dimension) {
+            (Some(values), Some(n)) if values.len() != n => {
+                fail(&format!("x0 has {} values for dimension {}", values.len(), n))
+            }
+            (Some(values), _) => values.clone(),
+            (None, Some(n)) => vec![0.0; n],
+            (None, None) => fail("an objective needs --x0 or --dimension"),
+        }
+    }
+
+    fn function(&self, values: &[f64]) -> Function {
+        match (&self.plugin, &self.expr) {
+            (Some(path), None) => {
+                let eval = plugin::load(path).unwrap_or_else(|e| fail(&e));
+                Box::new(move |x: &[f64]| eval(x.len(), x.as_ptr()))
+            }
+            (None, Some(expression)) => expression::parse(expression, values).unwrap_or_else(|e| fail(&e)),
+            _ => fail("expr and plugin are exclusive"),
+        }
+    }
+}
 
 fn main() {
-    let mut calls_count = Box::new(0);
+    let mut problem = Problem::default();
+    let mut config = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config = Some(argument(&mut args, &arg)),
+            "--expr" => problem.expr = Some(argument(&mut args, &arg)),
+            "--plugin" => problem.plugin = Some(argument(&mut args, &arg)),
+            "--x0" => problem.x0 = Some(parse_point(&argument(&mut args, &arg))),
+            "--dimension" => problem.dimension = Some(parse(&argument(&mut args, &arg))),
+            "--rhobeg" => problem.rhobeg = Some(parse(&argument(&mut args, &arg))),
+            "--rhoend" => problem.rhoend = Some(parse(&argument(&mut args, &arg))),
+            "--npt" => problem.npt = Some(parse(&argument(&mut args, &arg))),
+            "--budget" => problem.budget = Some(parse(&argument(&mut args, &arg))),
+            "--restarts" => problem.restarts = Some(parse(&argument(&mut args, &arg))),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unknown argument {}", arg)),
+        }
+    }
+    if let Some(path) = config {
+        problem.merge(config::load(&path).unwrap_or_else(|e| fail(&e)));
+    }
+    if problem.plugin.is_none() && problem.expr.is_none() {
+        return demo();
+    }
+    let mut values = problem.start();
+    let n = values.len();
+    let transforms = match problem.transforms.len() {
+        0 => vec![Transform::Identity; n],
+        count if count == n => problem.transforms.clone(),
+        count => fail(&format!("{} transforms for dimension {}", count, n)),
+    };
+    for (i, (v, t)) in values.iter().zip(transforms.iter()).enumerate() {
+        if !t.contains(*v) {
+            fail(&format!("x{} starts at {} outside the domain of {:?}", i, v, t))
+        }
+    }
+    let function = problem.function(&values);
+    let mut point = Vec::with_capacity(n);
+    let mut objective = |x: &[f64]| {
+        point.clear();
+        point.extend(x.iter().zip(transforms.iter()).map(|(v, t)| t.from_internal(*v)));
+        function(&point)
+    };
+    let mut internal = values.iter().zip(transforms.iter()).map(|(v, t)| t.to_internal(*v))
+        .collect::<Vec<_>>();
+    let mut solver = problem.solver(n);
+    let result = match problem.restarts {
+        None | Some(0) => solver.perform_mut(&mut internal, &mut objective),
+        Some(restarts_count) => Restarts::new()
+            .total_function_calls_count(solver.settings().max_function_calls_count)
+            .max_restarts_count(restarts_count)
+            .perform_mut(&mut solver, &mut internal, &mut objective)
+            .map(|report| {
+                println!("restarts_count: {}", report.restarts_count);
+                OptimizationResult {function_calls_count: report.function_calls_count, ..report.result}
+            }),
+    };
+    let result = result.unwrap_or_else(|e| fail(&e.to_string()));
+    for ((v, x), t) in values.iter_mut().zip(internal.iter()).zip(transforms.iter()) {
+        *v = t.from_internal(*x);
+    }
+    report(&values, &result);
+}
+
+fn demo() {
     let mut values = vec![0.0_f64, - 5.0_f64.sqrt()];
     println!("initial: {:?}", values);
-    let result = {
-        let mut function = |x: &[f64]| -> f64 {
+    let result = Newuoa::new()
+        .variables_count(2)
+        .number_of_interpolation_conditions((2 + 1)*(2 + 2)/2)
+        .initial_trust_region_radius(1e3)
+        .final_trust_region_radius(1e-3)
+        .max_function_calls_count(100)
+        .perform(&mut values, &|x: &[f64]| -> f64 {
             assert!(x.len() == 2);
-            *calls_count += 1;
             -4.0*x[0]*x[1] + 5.0*x[0]*x[0] + 8.0*x[1]*x[1]
                 + 16.0*(5.0_f64).sqrt()*x[0] + 8.0*(5.0_f64).sqrt()*x[1] - 44.0
-        };
-        newuoa::Newuoa::new()
-            .variables_count(2)
-            .number_of_interpolation_conditions((2 + 1)*(2 + 2)/2)
-            .initial_trust_region_radius(1e-3)
-            .final_trust_region_radius(1e3)
-            .max_function_calls_count(100)
-            .perform_mut(&mut values, &mut function)
-    };
+        })
+        .expect("invalid settings");
+    report(&values, &result);
+}
+
+fn report(values: &[f64], result: &OptimizationResult) {
     println!("final: {:?}", values);
-    println!("result: {}", result);
-    println!("calls_count: {}", *calls_count);
+    println!("result: {}", result.value);
+    println!("calls_count: {}", result.function_calls_count);
+    println!("final_trust_region_radius: {}", result.final_trust_region_radius);
+    println!("termination_reason: {:?}", result.termination_reason);
+}
+
+fn argument<I>(args: &mut I, name: &str) -> String where I: Iterator<Item = String> {
+    args.next().unwrap_or_else(|| fail(&format!("{} needs a value", name)))
+}
+
+fn parse<T>(value: &str) -> T where T: std::str::FromStr {
+    value.trim().parse().unwrap_or_else(|_| fail(&format!("invalid number {:?}", value)))
+}
+
+fn parse_point(value: &str) -> Vec<f64> {
+    value.split(',').map(parse).collect()
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("newuoa: {}\n{}", message, USAGE);
+    exit(2)
+}
+
+#[cfg(feature = "cli")]
+mod config {
+    use std::fs;
+    use std::path::Path;
+
+    use serde_json;
+    use toml;
+
+    use Problem;
+
+    pub fn load(path: &str) -> Result<Problem, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let result = if Path::new(path).extension().is_some_and(|v| v == "json") {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&content).map_err(|e| e.to_string())
+        };
+        result.map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+mod config {
+    use Problem;
+
+    pub fn load(_: &str) -> Result<Problem, String> {
+        Err("--config needs the cli feature".to_string())
+    }
+}
+
+#[cfg(feature = "cli")]
+mod expression {
+    use std::str::FromStr;
+
+    use meval::{Context, ContextProvider, Expr};
+
+    use Function;
+
+    /// Binds `x0`, `x1`, ... to the coordinates of the point.
+    struct Variables<'a>(&'a [f64]);
+
+    impl<'a> ContextProvider for Variables<'a> {
+        fn get_var(&self, name: &str) -> Option<f64> {
+            name.strip_prefix('x')?.parse::<usize>().ok().and_then(|i| self.0.get(i).cloned())
+        }
+    }
+
+    /// Evaluates the expression once at `values` so unknown names are reported before the
+    /// run, later failures can only come from the point and give NaN.
+    pub fn parse(expression: &str, values: &[f64]) -> Result<Function, String> {
+        let expression = Expr::from_str(expression).map_err(|e| e.to_string())?;
+        let context = Context::new();
+        expression.eval_with_context((Variables(values), &context)).map_err(|e| e.to_string())?;
+        Ok(Box::new(move |x: &[f64]| {
+            expression.eval_with_context((Variables(x), &context)).unwrap_or(f64::NAN)
+        }))
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+mod expression {
+    use Function;
+
+    pub fn parse(_: &str, _: &[f64]) -> Result<Function, String> {
+        Err("--expr needs the cli feature".to_string())
+    }
+}
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod plugin {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::{c_char, c_int, c_void};
+
+    pub type Eval = extern "C" fn(n: usize, x: *const f64) -> f64;
+
+    const RTLD_NOW: c_int = 2;
+
+    #[link(name = "dl")]
+    extern "C" {
+        fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        fn dlerror() -> *mut c_char;
+    }
+
+    /// Loads `eval` from the library at `path`, which stays loaded until the process exits.
+    /// The symbol is taken to have the `Eval` signature, nothing the loader can check.
+    pub fn load(path: &str) -> Result<Eval, String> {
+        let path = CString::new(path).map_err(|e| e.to_string())?;
+        unsafe {
+            let handle = dlopen(path.as_ptr(), RTLD_NOW);
+            if handle.is_null() {
+                return Err(last_error());
+            }
+            let symbol = dlsym(handle, b"eval\0".as_ptr() as *const c_char);
+            if symbol.is_null() {
+                return Err(last_error());
+            }
+            Ok(::std::mem::transmute::<*mut c_void, Eval>(symbol))
+        }
+    }
+
+    unsafe fn last_error() -> String {
+        let message = dlerror();
+        if message.is_null() {
+            "unknown dynamic loader error".to_string()
+        } else {
+            CStr::from_ptr(message).to_string_lossy().into_owned()
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod plugin {
+    pub type Eval = extern "C" fn(n: usize, x: *const f64) -> f64;
+
+    pub fn load(_: &str) -> Result<Eval, String> {
+        Err("plugins are only supported on linux and macos".to_string())
+    }
+}
+
+#[test]
+fn test_merge_should_keep_command_line_values() {
+    let mut problem = Problem {plugin: Some("objective.so".to_string()), rhobeg: Some(0.5),
+        ..Problem::default()};
+    problem.merge(Problem {
+        expr: Some("x0^2".to_string()),
+        x0: Some(vec![1.0]),
+        rhobeg: Some(2.0),
+        transforms: vec![Transform::Log],
+        ..Problem::default()
+    });
+    assert_eq!(problem.plugin, Some("objective.so".to_string()));
+    assert_eq!(problem.expr, None);
+    assert_eq!(problem.x0, Some(vec![1.0]));
+    assert_eq!(problem.rhobeg, Some(0.5));
+    assert_eq!(problem.transforms, vec![Transform::Log]);
+}
+
+#[test]
+fn test_merge_should_take_objective_from_config_when_unset() {
+    let mut problem = Problem::default();
+    problem.merge(Problem {expr: Some("x0^2".to_string()), ..Problem::default()});
+    assert_eq!(problem.expr, Some("x0^2".to_string()));
+    assert_eq!(problem.plugin, None);
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_config_load_should_read_toml_and_json() {
+    use std::fs;
+    let directory = env::temp_dir();
+    let toml_path = directory.join(format!("newuoa-config-{}.toml", std::process::id()));
+    fs::write(&toml_path, "expr = \"x0^2 + x1^2\"\nx0 = [1.0, 2.0]\nbudget = 50\n\
+        transforms = [\"Identity\", { Bounded = [0.0, 3.0] }]\n").unwrap();
+    let json_path = directory.join(format!("newuoa-config-{}.json", std::process::id()));
+    fs::write(&json_path, "{\"plugin\": \"objective.so\", \"dimension\": 3, \"restarts\": 2}").unwrap();
+    let toml = config::load(toml_path.to_str().unwrap());
+    let json = config::load(json_path.to_str().unwrap());
+    let _ = fs::remove_file(&toml_path);
+    let _ = fs::remove_file(&json_path);
+    let toml = toml.unwrap();
+    assert_eq!(toml.expr, Some("x0^2 + x1^2".to_string()));
+    assert_eq!(toml.x0, Some(vec![1.0, 2.0]));
+    assert_eq!(toml.budget, Some(50));
+    assert_eq!(toml.transforms, vec![Transform::Identity, Transform::Bounded(0.0, 3.0)]);
+    let json = json.unwrap();
+    assert_eq!(json.plugin, Some("objective.so".to_string()));
+    assert_eq!(json.dimension, Some(3));
+    assert_eq!(json.restarts, Some(2));
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_config_load_should_reject_unknown_fields() {
+    use std::fs;
+    let path = env::temp_dir().join(format!("newuoa-unknown-{}.toml", std::process::id()));
+    fs::write(&path, "rho_begin = 1.0\n").unwrap();
+    let result = config::load(path.to_str().unwrap());
+    let _ = fs::remove_file(&path);
+    assert!(result.is_err());
 }
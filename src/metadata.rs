@@ -0,0 +1,155 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use error::NewuoaError;
+use types::{Budget, Radius};
+
+pub const BACKEND: &str = "newuoa-cpp";
+
+/// Identifier of a run, unique within a process and very likely unique across
+/// processes: it mixes the process id, the start time and a per-process counter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RunId(u64);
+
+impl RunId {
+    pub fn generate() -> RunId {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let counter = COUNTER.fetch_add(1, Ordering::Relaxed) as u64;
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|v| v.as_nanos() as u64)
+            .unwrap_or(0);
+        let mut value = (process::id() as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ nanos;
+        value = (value ^ (value >> 29)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        RunId((value & !0xffff) | (counter & 0xffff))
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for RunId {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{:016x}", self.0)
+    }
+}
+
+/// Snapshot of the solver configuration a run was started with. Deserializing fails on
+/// settings `check` rejects.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SettingsFields"))]
+pub struct Settings {
+    pub variables_count: usize,
+    pub number_of_interpolation_conditions: usize,
+    pub initial_trust_region_radius: f64,
+    pub final_trust_region_radius: f64,
+    pub max_function_calls_count: usize,
+}
+
+impl Settings {
+    /// Checks what every run needs whatever variables it fixes: at least two variables,
+    /// `4 <= npt <= (n + 1)(n + 2)/2`, positive finite radii in order and a budget of at
+    /// least one call. A run checks the rest when it starts.
+    pub fn check(&self) -> Result<(), NewuoaError> {
+        let n = self.variables_count;
+        if n < 2 {
+            return Err(NewuoaError::DimensionTooSmall {variables_count: n});
+        }
+        let npt = self.number_of_interpolation_conditions;
+        let (min, max) = (4, (n + 1).saturating_mul(n + 2)/2);
+        if npt < min || npt > max {
+            return Err(NewuoaError::InvalidInterpolationConditions {
+                number_of_interpolation_conditions: npt,
+                min,
+                max,
+            });
+        }
+        Radius::try_from(self.initial_trust_region_radius)?;
+        Radius::try_from(self.final_trust_region_radius)?;
+        Budget::try_from(self.max_function_calls_count)?;
+        if self.initial_trust_region_radius < self.final_trust_region_radius {
+            return Err(NewuoaError::RadiusOrderInvalid {
+                initial_trust_region_radius: self.initial_trust_region_radius,
+                final_trust_region_radius: self.final_trust_region_radius,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// `Settings` as read, before `Settings::check`.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct SettingsFields {
+    variables_count: usize,
+    number_of_interpolation_conditions: usize,
+    initial_trust_region_radius: f64,
+    final_trust_region_radius: f64,
+    max_function_calls_count: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SettingsFields> for Settings {
+    type Error = NewuoaError;
+
+    fn try_from(value: SettingsFields) -> Result<Settings, NewuoaError> {
+        let settings = Settings {
+            variables_count: value.variables_count,
+            number_of_interpolation_conditions: value.number_of_interpolation_conditions,
+            initial_trust_region_radius: value.initial_trust_region_radius,
+            final_trust_region_radius: value.final_trust_region_radius,
+            max_function_calls_count: value.max_function_calls_count,
+        };
+        settings.check()?;
+        Ok(settings)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RunMetadata {
+    pub id: RunId,
+    /// Not deserialized, always the backend of this build.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing, default = "backend"))]
+    pub backend: &'static str,
+    pub started_at: SystemTime,
+    pub finished_at: Option<SystemTime>,
+    pub settings: Settings,
+}
+
+impl RunMetadata {
+    pub fn new(settings: Settings) -> RunMetadata {
+        RunMetadata {
+            id: RunId::generate(),
+            backend: BACKEND,
+            started_at: SystemTime::now(),
+            finished_at: None,
+            settings,
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.finished_at = Some(SystemTime::now());
+    }
+}
+
+#[cfg(feature = "serde")]
+fn backend() -> &'static str {
+    BACKEND
+}
+
+#[test]
+fn test_generated_run_ids_should_differ() {
+    let ids = (0..100).map(|_| RunId::generate()).collect::<Vec<_>>();
+    for (i, id) in ids.iter().enumerate() {
+        assert!(ids[i + 1..].iter().all(|v| v != id));
+    }
+}
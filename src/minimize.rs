@@ -0,0 +1,67 @@
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+/// Settings of `minimize`, heuristic defaults are used for the ones left `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Options {
+    /// Defaults to `2n + 1`, Powell's recommendation.
+    pub number_of_interpolation_conditions: Option<usize>,
+    /// Defaults to a tenth of the largest initial coordinate magnitude, at least `0.1`.
+    pub initial_trust_region_radius: Option<f64>,
+    /// Defaults to `1e-6` times the initial radius.
+    pub final_trust_region_radius: Option<f64>,
+    /// Defaults to `500n`.
+    pub max_function_calls_count: Option<usize>,
+}
+
+impl Options {
+    /// Solver configured for `values` with the defaults filled in, the radii by
+    /// `Newuoa::auto_radii`. Fails with the error the run would fail with on invalid
+    /// options.
+    pub fn solver(&self, values: &[f64]) -> Result<Newuoa, NewuoaError> {
+        let n = values.len();
+        let mut result = Newuoa::new();
+        result
+            .variables_count(n)
+            .number_of_interpolation_conditions(self.number_of_interpolation_conditions
+                .unwrap_or_else(|| (2*n + 1).min((n + 1)*(n + 2)/2).max(4)))
+            .auto_radii(values, 1e-6)
+            .max_function_calls_count(self.max_function_calls_count.unwrap_or((500*n).max(1)));
+        if let Some(value) = self.initial_trust_region_radius {
+            result.initial_trust_region_radius(value).final_trust_region_radius(1e-6*value);
+        }
+        if let Some(value) = self.final_trust_region_radius {
+            result.final_trust_region_radius(value);
+        }
+        result.validate(values)?;
+        Ok(result)
+    }
+}
+
+/// Minimizes `function` starting from `values` without setting up a `Newuoa`.
+pub fn minimize<F>(mut function: F, values: &mut [f64], options: Options)
+        -> Result<OptimizationResult, NewuoaError>
+        where F: FnMut(&[f64]) -> f64 {
+    options.solver(values)?.perform_mut(values, &mut function)
+}
+
+#[test]
+fn test_minimize_should_choose_settings_from_initial_point() {
+    let settings = Options::default().solver(&[0.0, 20.0, -3.0]).unwrap().settings();
+    assert_eq!(settings.number_of_interpolation_conditions, 7);
+    assert_eq!(settings.initial_trust_region_radius, 2.0);
+    assert_eq!(settings.final_trust_region_radius, 2e-6);
+    assert_eq!(settings.max_function_calls_count, 1500);
+    let mut values = [1.0, 1.0];
+    let result = minimize(|x: &[f64]| x[0]*x[0] + x[1]*x[1], &mut values, Options::default()).unwrap();
+    assert!(result.value < 2.0);
+}
+
+#[test]
+fn test_minimize_should_reject_invalid_radius() {
+    let options = Options {initial_trust_region_radius: Some(0.0), ..Options::default()};
+    assert_eq!(options.solver(&[1.0, 1.0]).err(), Some(NewuoaError::InvalidTrustRegionRadius {radius: 0.0}));
+    let result = minimize(|x: &[f64]| x[0]*x[0] + x[1]*x[1], &mut [1.0, 1.0], options);
+    assert_eq!(result, Err(NewuoaError::InvalidTrustRegionRadius {radius: 0.0}));
+}
@@ -0,0 +1,108 @@
+use Newuoa;
+use error::NewuoaError;
+use linalg::{cholesky, hessian, invert};
+
+pub use transforms::Transform;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    pub parameters: Vec<f64>,
+    pub log_likelihood: f64,
+    /// Square roots of the inverse observed information diagonal, `None` when the
+    /// numerical Hessian at the estimate is not positive definite.
+    pub standard_errors: Option<Vec<f64>>,
+}
+
+/// Maximum-likelihood estimation: maximizes `log_likelihood(parameters, data)` and
+/// computes standard errors from a finite-difference Hessian at the estimate.
+pub struct Mle {
+    transforms: Vec<Transform>,
+    hessian_step: f64,
+}
+
+impl Default for Mle {
+    fn default() -> Mle {
+        Mle::new()
+    }
+}
+
+impl Mle {
+    pub fn new() -> Mle {
+        Mle {
+            transforms: Vec::new(),
+            hessian_step: 1e-4,
+        }
+    }
+
+    pub fn transforms(&mut self, value: &[Transform]) -> &mut Self {
+        self.transforms = value.to_vec();
+        self
+    }
+
+    pub fn hessian_step(&mut self, value: f64) -> &mut Self {
+        assert!(value > 0.0);
+        self.hessian_step = value;
+        self
+    }
+
+    pub fn estimate<D, L>(&self, solver: &mut Newuoa, parameters: &mut [f64], data: &D,
+            log_likelihood: &L) -> Result<Estimate, NewuoaError>
+            where D: ?Sized, L: Fn(&[f64], &D) -> f64 {
+        let n = parameters.len();
+        assert!(self.transforms.is_empty() || self.transforms.len() == n);
+        let mut internal = (0..n).map(|i| self.transform(i).to_internal(parameters[i]))
+            .collect::<Vec<_>>();
+        {
+            let function = |x: &[f64]| -> f64 {
+                -log_likelihood(&self.to_parameters(x), data)
+            };
+            solver
+                .variables_count(n)
+                .number_of_interpolation_conditions(2*n + 1)
+                .perform(&mut internal, &function)?;
+        }
+        parameters.copy_from_slice(&self.to_parameters(&internal));
+        let negative = |x: &[f64]| -log_likelihood(x, data);
+        let information = hessian(&negative, parameters, self.hessian_step);
+        let standard_errors = cholesky(&information)
+            .and_then(|_| invert(&information))
+            .map(|inverse| (0..n).map(|i| inverse[i][i].sqrt()).collect());
+        Ok(Estimate {
+            parameters: parameters.to_vec(),
+            log_likelihood: log_likelihood(parameters, data),
+            standard_errors,
+        })
+    }
+
+    fn transform(&self, index: usize) -> Transform {
+        self.transforms.get(index).cloned().unwrap_or(Transform::Identity)
+    }
+
+    fn to_parameters(&self, x: &[f64]) -> Vec<f64> {
+        x.iter().enumerate().map(|(i, v)| self.transform(i).from_internal(*v)).collect()
+    }
+}
+
+#[test]
+fn test_estimate_should_recover_normal_parameters() {
+    let data = [1.2, 0.8, 1.5, 0.5, 1.0];
+    let log_likelihood = |p: &[f64], data: &[f64]| -> f64 {
+        data.iter()
+            .map(|x| -0.5*((x - p[0])/p[1]).powi(2) - p[1].ln())
+            .sum::<f64>()
+    };
+    let mut solver = Newuoa::new();
+    solver.final_trust_region_radius(1e-8).max_function_calls_count(2000);
+    let mut parameters = [0.0, 1.0];
+    let estimate = Mle::new()
+        .transforms(&[Transform::Identity, Transform::Log])
+        .estimate(&mut solver, &mut parameters, &data[..], &log_likelihood)
+        .unwrap();
+    let sigma = (data.iter().map(|x| (x - 1.0)*(x - 1.0)).sum::<f64>()/5.0).sqrt();
+    assert!((estimate.parameters[0] - 1.0).abs() < 1e-4);
+    assert!((estimate.parameters[1] - sigma).abs() < 1e-4);
+    assert_eq!(parameters.to_vec(), estimate.parameters);
+    let standard_errors = estimate.standard_errors.unwrap();
+    assert!((standard_errors[0] - sigma/5.0_f64.sqrt()).abs() < 1e-3);
+    assert!((standard_errors[1] - sigma/10.0_f64.sqrt()).abs() < 1e-3);
+}
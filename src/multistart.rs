@@ -0,0 +1,190 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use Newuoa;
+use error::NewuoaError;
+use random::Random;
+use result::OptimizationResult;
+use sobol::{self, Sobol};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sampling {
+    LatinHypercube,
+    /// Quasi-random points filling the box more evenly for few starts, deterministic so
+    /// the seed is ignored. Limited to `MultiStart::MAX_SOBOL_DIMENSION` variables.
+    Sobol,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiStartReport {
+    pub starts: Vec<Vec<f64>>,
+    /// Local result of every start that received enough budget, in start order.
+    pub results: Vec<OptimizationResult>,
+    pub function_calls_count: usize,
+}
+
+impl MultiStartReport {
+    pub fn best(&self) -> Option<&OptimizationResult> {
+        self.results.iter().min_by(|a, b| a.value.total_cmp(&b.value))
+    }
+}
+
+/// Runs the solver from start points sampled in the box `[lower, upper]`, splitting one
+/// total evaluation budget evenly over the remaining starts so that budget left unused by
+/// early convergence goes to later starts.
+pub struct MultiStart {
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    sampling: Sampling,
+    starts_count: usize,
+    total_function_calls_count: usize,
+    seed: u64,
+}
+
+impl MultiStart {
+    pub const MAX_SOBOL_DIMENSION: usize = sobol::MAX_DIMENSION;
+
+    pub fn new(lower: &[f64], upper: &[f64]) -> MultiStart {
+        assert_eq!(lower.len(), upper.len());
+        assert!(lower.iter().zip(upper.iter()).all(|(l, u)| l < u));
+        MultiStart {
+            lower: lower.to_vec(),
+            upper: upper.to_vec(),
+            sampling: Sampling::LatinHypercube,
+            starts_count: 10,
+            total_function_calls_count: 10000,
+            seed: 0,
+        }
+    }
+
+    pub fn sampling(&mut self, value: Sampling) -> &mut Self {
+        self.sampling = value;
+        self
+    }
+
+    pub fn starts_count(&mut self, value: usize) -> &mut Self {
+        assert!(value >= 1);
+        self.starts_count = value;
+        self
+    }
+
+    pub fn total_function_calls_count(&mut self, value: usize) -> &mut Self {
+        self.total_function_calls_count = value;
+        self
+    }
+
+    pub fn seed(&mut self, value: u64) -> &mut Self {
+        self.seed = value;
+        self
+    }
+
+    pub fn starts(&self) -> Vec<Vec<f64>> {
+        match self.sampling {
+            Sampling::LatinHypercube =>
+                Random::new(self.seed).latin_hypercube(&self.lower, &self.upper, self.starts_count),
+            Sampling::Sobol => {
+                let mut sobol = Sobol::new(self.lower.len());
+                (0..self.starts_count)
+                    .map(|_| {
+                        sobol.next_point().iter().zip(self.lower.iter().zip(self.upper.iter()))
+                            .map(|(t, (l, u))| l + (u - l)*t)
+                            .collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Runs the starts on the rayon thread pool, each with an equal share of the total
+    /// budget and its own copy of `solver` holding the working space. Observers are not
+    /// copied.
+    #[cfg(feature = "rayon")]
+    pub fn par_perform<F>(&self, solver: &Newuoa, function: &F)
+            -> Result<MultiStartReport, NewuoaError>
+            where F: Fn(&[f64]) -> f64 + Sync {
+        let n = self.lower.len();
+        let npt = 2*n + 1;
+        let starts = self.starts();
+        let share = self.total_function_calls_count/starts.len();
+        let runs = starts.iter()
+            .filter(|_| share > npt)
+            .map(|start| {
+                let mut solver = solver.detached();
+                solver
+                    .variables_count(n)
+                    .number_of_interpolation_conditions(npt)
+                    .max_function_calls_count(share);
+                (solver, start.clone())
+            })
+            .collect::<Vec<_>>();
+        let results = runs.into_par_iter()
+            .map(|(mut solver, mut start)| solver.perform(&mut start, function))
+            .collect::<Result<Vec<_>, _>>()?;
+        let function_calls_count = results.iter().map(|v| v.function_calls_count).sum();
+        Ok(MultiStartReport {starts, results, function_calls_count})
+    }
+
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, function: &mut F)
+            -> Result<MultiStartReport, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let n = self.lower.len();
+        let npt = 2*n + 1;
+        solver
+            .variables_count(n)
+            .number_of_interpolation_conditions(npt);
+        let starts = self.starts();
+        let mut results = Vec::with_capacity(starts.len());
+        let mut remaining = self.total_function_calls_count;
+        for (k, start) in starts.iter().enumerate() {
+            let share = remaining/(starts.len() - k);
+            if share < npt + 1 {
+                continue;
+            }
+            let result = solver
+                .max_function_calls_count(share)
+                .perform_mut(&mut start.clone(), function)?;
+            remaining -= result.function_calls_count.min(remaining);
+            results.push(result);
+        }
+        Ok(MultiStartReport {
+            starts,
+            results,
+            function_calls_count: self.total_function_calls_count - remaining,
+        })
+    }
+}
+
+#[test]
+fn test_multi_start_should_share_budget_and_find_best() {
+    let report = MultiStart::new(&[-3.0, -3.0], &[3.0, 3.0])
+        .starts_count(4)
+        .total_function_calls_count(100)
+        .seed(1)
+        .perform_mut(&mut Newuoa::new(), &mut |x: &[f64]| (x[0]*x[0] - 1.0).powi(2) + x[1]*x[1])
+        .unwrap();
+    assert_eq!(report.starts.len(), 4);
+    assert!(report.function_calls_count <= 100);
+    let best = report.best().unwrap();
+    assert!(report.results.iter().all(|r| r.value >= best.value));
+}
+
+#[test]
+fn test_sobol_starts_should_fill_box() {
+    let starts = MultiStart::new(&[0.0, -2.0], &[4.0, 2.0])
+        .sampling(Sampling::Sobol)
+        .starts_count(3)
+        .starts();
+    assert_eq!(starts, vec![vec![2.0, 0.0], vec![3.0, -1.0], vec![1.0, 1.0]]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_perform_should_run_every_start() {
+    let report = MultiStart::new(&[-3.0, -3.0], &[3.0, 3.0])
+        .starts_count(4)
+        .total_function_calls_count(100)
+        .par_perform(&Newuoa::new(), &|x: &[f64]| (x[0]*x[0] - 1.0).powi(2) + x[1]*x[1])
+        .unwrap();
+    assert_eq!(report.results.len(), 4);
+    assert!(report.function_calls_count <= 100);
+}
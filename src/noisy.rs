@@ -0,0 +1,106 @@
+use progress::Progress;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Mean,
+    /// More robust to occasional outliers of Monte Carlo estimates.
+    Median,
+}
+
+/// Objective wrapper for noisy objectives, replicating every evaluation and returning the
+/// mean or median. The schedule raises the number of replications as the solver closes
+/// in, using the same trust region radius estimate as `IterationInfo::rho`. Like
+/// `Instrumented`, pass `as_fn_mut()` where an `FnMut(&[f64]) -> f64` is expected.
+pub struct NoisyObjective<F> {
+    function: F,
+    replications_count: usize,
+    aggregate: Aggregate,
+    schedule: Vec<(f64, usize)>,
+    progress: Progress,
+    calls_count: usize,
+}
+
+impl<F> NoisyObjective<F> where F: FnMut(&[f64]) -> f64 {
+    pub fn new(function: F) -> NoisyObjective<F> {
+        NoisyObjective {
+            function,
+            replications_count: 1,
+            aggregate: Aggregate::Mean,
+            schedule: Vec::new(),
+            progress: Progress::new(f64::INFINITY),
+            calls_count: 0,
+        }
+    }
+
+    pub fn replications_count(mut self, value: usize) -> Self {
+        assert!(value >= 1);
+        self.replications_count = value;
+        self
+    }
+
+    pub fn aggregate(mut self, value: Aggregate) -> Self {
+        self.aggregate = value;
+        self
+    }
+
+    /// Pairs `(rho, replications_count)`: once the radius estimate drops to `rho` or below,
+    /// at least that many replications are made.
+    pub fn schedule(mut self, value: &[(f64, usize)]) -> Self {
+        self.schedule = value.to_vec();
+        self
+    }
+
+    pub fn current_replications_count(&self) -> usize {
+        self.schedule.iter()
+            .filter(|v| self.progress.rho <= v.0)
+            .map(|v| v.1)
+            .fold(self.replications_count, usize::max)
+    }
+
+    pub fn call(&mut self, x: &[f64]) -> f64 {
+        let count = self.current_replications_count();
+        let mut values = (0..count).map(|_| (self.function)(x)).collect::<Vec<_>>();
+        self.calls_count += count;
+        let value = match self.aggregate {
+            Aggregate::Mean => values.iter().sum::<f64>()/count as f64,
+            Aggregate::Median => {
+                values.sort_by(|a, b| a.total_cmp(b));
+                if count % 2 == 1 {
+                    values[count/2]
+                } else {
+                    (values[count/2 - 1] + values[count/2])/2.0
+                }
+            }
+        };
+        self.progress.update(x, value);
+        value
+    }
+
+    pub fn as_fn_mut<'a>(&'a mut self) -> impl FnMut(&[f64]) -> f64 + 'a where F: 'a {
+        move |x: &[f64]| self.call(x)
+    }
+
+    /// Calls of the wrapped objective, counting every replication.
+    pub fn calls_count(&self) -> usize {
+        self.calls_count
+    }
+
+    pub fn into_inner(self) -> F {
+        self.function
+    }
+}
+
+#[test]
+fn test_noisy_objective_should_replicate_more_for_small_steps() {
+    let mut noise = [3.0, -3.0, 0.0, 100.0].iter().cycle();
+    let mut objective = NoisyObjective::new(|x: &[f64]| x[0] + noise.next().unwrap())
+        .replications_count(1)
+        .aggregate(Aggregate::Median)
+        .schedule(&[(0.1, 3)]);
+    assert_eq!(objective.call(&[1.0]), 4.0);
+    assert_eq!(objective.call(&[2.0]), -1.0);
+    objective.call(&[2.05]);
+    assert_eq!(objective.current_replications_count(), 3);
+    assert_eq!(objective.call(&[1.0]), 4.0);
+    assert_eq!(objective.calls_count(), 6);
+}
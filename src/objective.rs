@@ -0,0 +1,50 @@
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+/// Objective carrying its own state and dimension, as an alternative to closures. Front
+/// ends taking closures accept `|x: &[f64]| objective.eval(x)`.
+pub trait Objective {
+    fn dim(&self) -> usize;
+
+    fn eval(&mut self, x: &[f64]) -> f64;
+}
+
+impl Newuoa {
+    /// `perform_mut` with the variables count set to `objective.dim()`.
+    pub fn perform_objective<O>(&mut self, values: &mut [f64], objective: &mut O)
+            -> Result<OptimizationResult, NewuoaError>
+            where O: Objective + ?Sized {
+        self.variables_count(objective.dim())
+            .perform_mut(values, &mut |x: &[f64]| objective.eval(x))
+    }
+}
+
+#[test]
+fn test_perform_objective_should_use_dimension_and_state() {
+    struct Sphere {
+        calls_count: usize,
+    }
+
+    impl Objective for Sphere {
+        fn dim(&self) -> usize {
+            3
+        }
+
+        fn eval(&mut self, x: &[f64]) -> f64 {
+            self.calls_count += 1;
+            x.iter().map(|v| v*v).sum()
+        }
+    }
+
+    let mut sphere = Sphere {calls_count: 0};
+    let mut values = [1.0, 1.0, 1.0, 7.0];
+    let result = Newuoa::new()
+        .number_of_interpolation_conditions(7)
+        .max_function_calls_count(40)
+        .perform_objective(&mut values, &mut sphere)
+        .unwrap();
+    assert_eq!(sphere.calls_count, 40);
+    assert_eq!(result.solution.len(), 3);
+    assert_eq!(values[3], 7.0);
+}
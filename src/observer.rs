@@ -0,0 +1,76 @@
+use std::io::Write;
+use std::ops::ControlFlow;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use metadata::RunId;
+
+/// Progress report passed to an observer after every objective evaluation.
+/// Serializable but not deserializable as it borrows the points.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct IterationInfo<'a> {
+    /// Number of evaluations past the initial interpolation set, each following a trust
+    /// region or model improvement step.
+    pub iteration: usize,
+    pub function_calls_count: usize,
+    pub point: &'a [f64],
+    pub value: f64,
+    pub best_point: &'a [f64],
+    pub best_value: f64,
+    /// Estimate of the trust region radius, see `Progress::rho`.
+    pub rho: f64,
+    pub run_id: RunId,
+}
+
+/// Owned copy of an `IterationInfo`, e.g. to send it to another thread.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct IterationUpdate {
+    pub iteration: usize,
+    pub function_calls_count: usize,
+    pub point: Vec<f64>,
+    pub value: f64,
+    pub best_point: Vec<f64>,
+    pub best_value: f64,
+    pub rho: f64,
+    pub run_id: RunId,
+}
+
+impl<'a> IterationInfo<'a> {
+    pub fn to_update(&self) -> IterationUpdate {
+        IterationUpdate {
+            iteration: self.iteration,
+            function_calls_count: self.function_calls_count,
+            point: self.point.to_vec(),
+            value: self.value,
+            best_point: self.best_point.to_vec(),
+            best_value: self.best_value,
+            rho: self.rho,
+            run_id: self.run_id,
+        }
+    }
+}
+
+pub type Observer = Box<dyn FnMut(&IterationInfo) -> ControlFlow<()> + Send>;
+
+/// Destination of the `Verbosity` reports, see `Newuoa::diagnostics`.
+pub type Diagnostics = Box<dyn Write + Send>;
+
+/// Observer return values: `()` always continues, `ControlFlow::Break(())` stops the run.
+pub trait IntoControlFlow {
+    fn into_control_flow(self) -> ControlFlow<()>;
+}
+
+impl IntoControlFlow for () {
+    fn into_control_flow(self) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl IntoControlFlow for ControlFlow<()> {
+    fn into_control_flow(self) -> ControlFlow<()> {
+        self
+    }
+}
@@ -0,0 +1,77 @@
+use std::panic::resume_unwind;
+use std::thread;
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+use run::Space;
+
+impl Newuoa {
+    /// `perform` evaluating the points of the initial interpolation set on their own
+    /// threads before the run: the starting point and its steps of the initial radius
+    /// along each axis, `2n + 1` at most. The run then takes those values instead of
+    /// calling `function` again, so only the later evaluations are sequential.
+    pub fn perform_parallel_initial<F>(&mut self, values: &mut [f64], function: &F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: Fn(&[f64]) -> f64 + Sync {
+        self.validate(values)?;
+        let points = self.initial_points(values);
+        let evaluated = thread::scope(|scope| {
+            let handles = points.iter()
+                .map(|point| scope.spawn(move || function(point)))
+                .collect::<Vec<_>>();
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|payload| resume_unwind(payload)))
+                .collect::<Vec<_>>()
+        });
+        let mut initial = points.into_iter().zip(evaluated).collect::<Vec<_>>();
+        self.perform_mut(values, &mut |x: &[f64]| {
+            match initial.iter().position(|v| v.0 == x) {
+                Some(index) => initial.swap_remove(index).1,
+                None => function(x),
+            }
+        })
+    }
+
+    /// Computed like the native routine does so the points compare equal to the ones it
+    /// asks for.
+    fn initial_points(&self, values: &[f64]) -> Vec<Vec<f64>> {
+        let space = Space {
+            variables_count: self.variables_count,
+            scaling: self.scaling.as_ref().map(|v| &v[..]),
+            fixed_variables: &self.fixed_variables,
+        };
+        let start = space.to_internal(values);
+        let n = start.len();
+        let radius = self.initial_trust_region_radius;
+        let count = self.number_of_interpolation_conditions
+            .min(2*n + 1)
+            .min(self.max_function_calls_count);
+        (0..count)
+            .map(|k| {
+                let mut x = start.clone();
+                if k >= 1 && k <= n {
+                    x[k - 1] += radius;
+                } else if k > n {
+                    x[k - n - 1] -= radius;
+                }
+                let mut point = Vec::with_capacity(self.variables_count);
+                space.to_external(&x, &mut point);
+                point
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_perform_parallel_initial_should_match_sequential_run() {
+    let function = |x: &[f64]| (x[0] - 1.0).powi(2) + 10.0*(x[1] + 0.5).powi(2) + x[2]*x[2];
+    let mut solver = Newuoa::for_dimension(3);
+    solver.max_function_calls_count(50);
+    let mut sequential = [0.0, 0.0, 0.0];
+    let expected = solver.perform(&mut sequential, &function).unwrap();
+    let mut parallel = [0.0, 0.0, 0.0];
+    let result = solver.perform_parallel_initial(&mut parallel, &function).unwrap();
+    assert_eq!(result.solution, expected.solution);
+    assert_eq!(result.function_calls_count, expected.function_calls_count);
+}
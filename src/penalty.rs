@@ -0,0 +1,128 @@
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+pub type ConstraintFunction = Box<dyn Fn(&[f64]) -> f64>;
+
+pub enum Constraint {
+    /// Satisfied when `g(x) <= 0`.
+    Inequality(ConstraintFunction),
+    /// Satisfied when `h(x) = 0`.
+    Equality(ConstraintFunction),
+}
+
+impl Constraint {
+    pub fn inequality<G>(value: G) -> Constraint where G: Fn(&[f64]) -> f64 + 'static {
+        Constraint::Inequality(Box::new(value))
+    }
+
+    pub fn equality<H>(value: H) -> Constraint where H: Fn(&[f64]) -> f64 + 'static {
+        Constraint::Equality(Box::new(value))
+    }
+
+    /// Zero when satisfied, otherwise the distance of the constraint value to feasibility.
+    pub fn violation(&self, x: &[f64]) -> f64 {
+        match *self {
+            Constraint::Inequality(ref g) => g(x).max(0.0),
+            Constraint::Equality(ref h) => h(x).abs(),
+        }
+    }
+}
+
+/// Objective plus `weight` times the sum of squared constraint violations.
+pub fn penalized<'a, F>(objective: F, constraints: &'a [Constraint], weight: f64)
+        -> impl FnMut(&[f64]) -> f64 + 'a
+        where F: FnMut(&[f64]) -> f64 + 'a {
+    let mut objective = objective;
+    move |x: &[f64]| {
+        objective(x) + weight*constraints.iter().map(|c| c.violation(x).powi(2)).sum::<f64>()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PenaltyReport {
+    /// Result of the last stage, its value includes the penalty.
+    pub result: OptimizationResult,
+    /// Objective without the penalty at the solution.
+    pub objective: f64,
+    pub violations: Vec<f64>,
+    /// Function calls across all stages, plus the one computing `objective`.
+    pub function_calls_count: usize,
+}
+
+/// Quadratic penalty method: minimizes the penalized objective once per weight of the
+/// schedule, starting every stage from the previous solution, and stops early once all
+/// violations are within the tolerance.
+pub struct PenaltyMethod {
+    weights: Vec<f64>,
+    tolerance: f64,
+}
+
+impl Default for PenaltyMethod {
+    fn default() -> PenaltyMethod {
+        PenaltyMethod::new()
+    }
+}
+
+impl PenaltyMethod {
+    pub fn new() -> PenaltyMethod {
+        PenaltyMethod {
+            weights: vec![1.0, 1e1, 1e2, 1e3, 1e4],
+            tolerance: 1e-6,
+        }
+    }
+
+    pub fn weights(&mut self, value: &[f64]) -> &mut Self {
+        assert!(!value.is_empty() && value.iter().all(|v| *v > 0.0));
+        self.weights = value.to_vec();
+        self
+    }
+
+    pub fn tolerance(&mut self, value: f64) -> &mut Self {
+        assert!(value >= 0.0);
+        self.tolerance = value;
+        self
+    }
+
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [f64], objective: &mut F,
+            constraints: &[Constraint]) -> Result<PenaltyReport, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let mut function_calls_count = 0;
+        let mut last = None;
+        for &weight in self.weights.iter() {
+            let result = solver.perform_mut(values, &mut penalized(&mut *objective, constraints, weight))?;
+            function_calls_count += result.function_calls_count;
+            let feasible = constraints.iter().all(|c| c.violation(values) <= self.tolerance);
+            last = Some(result);
+            if feasible {
+                break;
+            }
+        }
+        Ok(PenaltyReport {
+            result: last.expect("weights are not empty"),
+            objective: objective(values),
+            violations: constraints.iter().map(|c| c.violation(values)).collect(),
+            function_calls_count: function_calls_count + 1,
+        })
+    }
+}
+
+#[test]
+fn test_penalty_method_should_approach_feasible_point() {
+    let constraints = [Constraint::equality(|x: &[f64]| x[0] + x[1] - 1.0)];
+    let mut values = [0.0, 0.0];
+    let mut calls_count = 0;
+    let report = PenaltyMethod::new()
+        .tolerance(1e-2)
+        .perform_mut(Newuoa::new().max_function_calls_count(100), &mut values,
+            &mut |x: &[f64]| {
+                calls_count += 1;
+                x[0]*x[0] + x[1]*x[1]
+            }, &constraints)
+        .unwrap();
+    assert!(report.violations[0] <= 1e-2);
+    assert_eq!(report.violations[0], (values[0] + values[1] - 1.0).abs());
+    assert_eq!(report.objective, values[0]*values[0] + values[1]*values[1]);
+    assert!((report.objective - 0.5).abs() < 1e-2);
+    assert_eq!(report.function_calls_count, calls_count);
+}
@@ -0,0 +1,196 @@
+use Newuoa;
+use error::NewuoaError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gains {
+    pub proportional: f64,
+    pub integral: f64,
+    pub derivative: f64,
+}
+
+impl Gains {
+    pub fn new(proportional: f64, integral: f64, derivative: f64) -> Gains {
+        Gains {proportional, integral, derivative}
+    }
+
+    fn to_array(self) -> [f64; 3] {
+        [self.proportional, self.integral, self.derivative]
+    }
+
+    fn from_array(values: [f64; 3]) -> Gains {
+        Gains::new(values[0], values[1], values[2])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Criterion {
+    /// Integral of squared error.
+    Ise,
+    /// Integral of time-weighted absolute error.
+    Itae,
+}
+
+/// Tunes PID gains against a closed-loop simulation returning `(time, output)` samples
+/// of the step response. Gains are searched in the unit box spanned by the gain bounds,
+/// so the solver radii are relative to the bounds width.
+pub struct PidTuner {
+    setpoint: f64,
+    criterion: Criterion,
+    overshoot_penalty: f64,
+    lower_bounds: Gains,
+    upper_bounds: Gains,
+}
+
+impl Default for PidTuner {
+    fn default() -> PidTuner {
+        PidTuner::new()
+    }
+}
+
+impl PidTuner {
+    pub fn new() -> PidTuner {
+        PidTuner {
+            setpoint: 1.0,
+            criterion: Criterion::Ise,
+            overshoot_penalty: 10.0,
+            lower_bounds: Gains::new(0.0, 0.0, 0.0),
+            upper_bounds: Gains::new(10.0, 10.0, 10.0),
+        }
+    }
+
+    pub fn setpoint(&mut self, value: f64) -> &mut Self {
+        self.setpoint = value;
+        self
+    }
+
+    pub fn criterion(&mut self, value: Criterion) -> &mut Self {
+        self.criterion = value;
+        self
+    }
+
+    pub fn overshoot_penalty(&mut self, value: f64) -> &mut Self {
+        assert!(value >= 0.0);
+        self.overshoot_penalty = value;
+        self
+    }
+
+    pub fn gain_bounds(&mut self, lower: Gains, upper: Gains) -> &mut Self {
+        for (l, u) in lower.to_array().iter().zip(upper.to_array().iter()) {
+            assert!(l < u);
+        }
+        self.lower_bounds = lower;
+        self.upper_bounds = upper;
+        self
+    }
+
+    pub fn cost(&self, response: &[(f64, f64)]) -> f64 {
+        let mut integral = 0.0;
+        let mut peak = f64::NEG_INFINITY;
+        let mut previous_time = response.first().map(|v| v.0).unwrap_or(0.0);
+        for &(time, output) in response.iter() {
+            let error = self.setpoint - output;
+            let dt = time - previous_time;
+            integral += match self.criterion {
+                Criterion::Ise => error*error*dt,
+                Criterion::Itae => time*error.abs()*dt,
+            };
+            previous_time = time;
+            peak = peak.max(output);
+        }
+        let scale = if self.setpoint != 0.0 { self.setpoint.abs() } else { 1.0 };
+        let overshoot = ((peak - self.setpoint)/scale).max(0.0);
+        integral + self.overshoot_penalty*overshoot*overshoot
+    }
+
+    /// Runs `solver` over the three gains with seven interpolation conditions, its own
+    /// variables and interpolation conditions counts are restored afterwards.
+    pub fn tune<F>(&self, solver: &mut Newuoa, gains: &mut Gains, simulate: &mut F)
+            -> Result<f64, NewuoaError>
+            where F: FnMut(&Gains) -> Vec<(f64, f64)> {
+        let lower = self.lower_bounds.to_array();
+        let upper = self.upper_bounds.to_array();
+        let initial = gains.to_array();
+        let mut values = [0.0; 3];
+        for i in 0..3 {
+            values[i] = ((initial[i] - lower[i])/(upper[i] - lower[i])).clamp(0.0, 1.0);
+        }
+        let variables_count = solver.variables_count;
+        let number_of_interpolation_conditions = solver.number_of_interpolation_conditions;
+        let result = {
+            let mut function = |x: &[f64]| -> f64 {
+                let (gains, outside) = PidTuner::to_gains(x, &lower, &upper);
+                self.cost(&simulate(&gains)) + 1e3*outside
+            };
+            solver
+                .variables_count(3)
+                .number_of_interpolation_conditions(7)
+                .perform_mut(&mut values, &mut function)
+        };
+        solver
+            .variables_count(variables_count)
+            .number_of_interpolation_conditions(number_of_interpolation_conditions);
+        let result = result?.value;
+        *gains = PidTuner::to_gains(&values, &lower, &upper).0;
+        Ok(result)
+    }
+
+    fn to_gains(x: &[f64], lower: &[f64; 3], upper: &[f64; 3]) -> (Gains, f64) {
+        let mut values = [0.0; 3];
+        let mut outside = 0.0;
+        for i in 0..3 {
+            let clamped = x[i].clamp(0.0, 1.0);
+            outside += (x[i] - clamped)*(x[i] - clamped);
+            values[i] = lower[i] + (upper[i] - lower[i])*clamped;
+        }
+        (Gains::from_array(values), outside)
+    }
+}
+
+#[test]
+fn test_cost_should_penalize_overshoot() {
+    let response = [(0.0, 0.0), (1.0, 1.5), (2.0, 1.0)];
+    let mut tuner = PidTuner::new();
+    tuner.overshoot_penalty(0.0);
+    let plain = tuner.cost(&response);
+    tuner.overshoot_penalty(4.0);
+    assert_eq!(tuner.cost(&response), plain + 1.0);
+    assert_eq!(plain, 0.25);
+}
+
+#[cfg(test)]
+fn simulate_first_order_plant(gains: &Gains) -> Vec<(f64, f64)> {
+    let dt = 0.01;
+    let mut output = 0.0;
+    let mut integral = 0.0;
+    let mut previous_error = 1.0;
+    let mut response = vec![(0.0, output)];
+    for step in 1..1000 {
+        let error = 1.0 - output;
+        integral += error*dt;
+        let control = gains.proportional*error + gains.integral*integral
+            + gains.derivative*(error - previous_error)/dt;
+        previous_error = error;
+        output += (control - output)*dt;
+        response.push((step as f64*dt, output));
+    }
+    response
+}
+
+#[test]
+fn test_tune_should_lower_first_order_plant_cost() {
+    let tuner = PidTuner::new();
+    let initial = Gains::new(1.0, 0.0, 0.0);
+    let initial_cost = tuner.cost(&simulate_first_order_plant(&initial));
+    let mut solver = Newuoa::new();
+    solver.variables_count(5).number_of_interpolation_conditions(11);
+    solver.max_function_calls_count(100);
+    let mut gains = initial;
+    let cost = tuner.tune(&mut solver, &mut gains, &mut simulate_first_order_plant).unwrap();
+    assert!(cost < initial_cost/2.0);
+    assert_eq!(tuner.cost(&simulate_first_order_plant(&gains)), cost);
+    for &value in gains.to_array().iter() {
+        assert!((0.0..=10.0).contains(&value));
+    }
+    assert_eq!(solver.variables_count, 5);
+    assert_eq!(solver.number_of_interpolation_conditions, 11);
+}
@@ -0,0 +1,148 @@
+use Newuoa;
+use error::NewuoaError;
+
+/// Maps unconstrained logits onto the probability simplex: the result is non-negative
+/// and sums to one.
+pub fn simplex_weights(logits: &[f64]) -> Vec<f64> {
+    let max = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let exps = logits.iter().map(|v| (v - max).exp()).collect::<Vec<_>>();
+    let sum = exps.iter().sum::<f64>();
+    exps.iter().map(|v| v/sum).collect()
+}
+
+/// Inverse of `simplex_weights` up to a constant shift of the logits.
+pub fn simplex_logits(weights: &[f64]) -> Vec<f64> {
+    weights.iter().map(|v| v.max(1e-12).ln()).collect()
+}
+
+/// Optimizes portfolio weights that must be non-negative and sum to one. The objective
+/// closure returns `(expected_return, risk)` for given weights, and the run minimizes
+/// `risk_aversion*risk - expected_return`.
+pub struct Portfolio {
+    risk_aversion: f64,
+    lower_bounds: Option<Vec<f64>>,
+    upper_bounds: Option<Vec<f64>>,
+    bounds_penalty: f64,
+}
+
+impl Default for Portfolio {
+    fn default() -> Portfolio {
+        Portfolio::new()
+    }
+}
+
+impl Portfolio {
+    pub fn new() -> Portfolio {
+        Portfolio {
+            risk_aversion: 1.0,
+            lower_bounds: None,
+            upper_bounds: None,
+            bounds_penalty: 1e3,
+        }
+    }
+
+    pub fn risk_aversion(&mut self, value: f64) -> &mut Self {
+        assert!(value >= 0.0);
+        self.risk_aversion = value;
+        self
+    }
+
+    pub fn lower_bounds(&mut self, value: &[f64]) -> &mut Self {
+        assert!(value.iter().all(|v| *v >= 0.0 && *v <= 1.0));
+        self.lower_bounds = Some(value.to_vec());
+        self
+    }
+
+    pub fn upper_bounds(&mut self, value: &[f64]) -> &mut Self {
+        assert!(value.iter().all(|v| *v >= 0.0 && *v <= 1.0));
+        self.upper_bounds = Some(value.to_vec());
+        self
+    }
+
+    pub fn bounds_penalty(&mut self, value: f64) -> &mut Self {
+        assert!(value >= 0.0);
+        self.bounds_penalty = value;
+        self
+    }
+
+    pub fn cost(&self, weights: &[f64], expected_return: f64, risk: f64) -> f64 {
+        self.risk_aversion*risk - expected_return + self.bounds_penalty*self.violation(weights)
+    }
+
+    pub fn optimize<F>(&self, solver: &mut Newuoa, weights: &mut [f64], objective: &mut F)
+            -> Result<f64, NewuoaError>
+            where F: FnMut(&[f64]) -> (f64, f64) {
+        let n = weights.len();
+        let mut logits = simplex_logits(weights);
+        let result = {
+            let mut function = |x: &[f64]| -> f64 {
+                let weights = simplex_weights(x);
+                let (expected_return, risk) = objective(&weights);
+                self.cost(&weights, expected_return, risk)
+            };
+            solver
+                .variables_count(n)
+                .number_of_interpolation_conditions(2*n + 1)
+                .perform_mut(&mut logits, &mut function)?
+                .value
+        };
+        weights.copy_from_slice(&simplex_weights(&logits));
+        Ok(result)
+    }
+
+    fn violation(&self, weights: &[f64]) -> f64 {
+        let mut result = 0.0;
+        if let Some(ref lower) = self.lower_bounds {
+            for (w, l) in weights.iter().zip(lower.iter()) {
+                result += (l - w).max(0.0).powi(2);
+            }
+        }
+        if let Some(ref upper) = self.upper_bounds {
+            for (w, u) in weights.iter().zip(upper.iter()) {
+                result += (w - u).max(0.0).powi(2);
+            }
+        }
+        result
+    }
+}
+
+#[test]
+fn test_simplex_weights_should_be_non_negative_and_sum_to_one() {
+    let weights = simplex_weights(&[-3.0, 0.0, 2.0, 700.0]);
+    assert!(weights.iter().all(|v| *v >= 0.0));
+    assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    let restored = simplex_weights(&simplex_logits(&[0.2, 0.3, 0.5]));
+    for (a, b) in restored.iter().zip([0.2, 0.3, 0.5].iter()) {
+        assert!((a - b).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_optimize_should_beat_equal_weights() {
+    let returns = [0.1, 0.05, 0.02];
+    let covariance = [[0.04, 0.006, 0.0], [0.006, 0.01, 0.001], [0.0, 0.001, 0.0025]];
+    let mut objective = |w: &[f64]| {
+        let expected_return = w.iter().zip(returns.iter()).map(|(w, r)| w*r).sum::<f64>();
+        let mut risk = 0.0;
+        for i in 0..3 {
+            for j in 0..3 {
+                risk += w[i]*covariance[i][j]*w[j];
+            }
+        }
+        (expected_return, risk)
+    };
+    let mut portfolio = Portfolio::new();
+    portfolio.risk_aversion(5.0);
+    let equal = [1.0/3.0; 3];
+    let (expected_return, risk) = objective(&equal);
+    let equal_cost = portfolio.cost(&equal, expected_return, risk);
+    let mut weights = equal;
+    let cost = portfolio
+        .optimize(Newuoa::new().max_function_calls_count(200), &mut weights, &mut objective)
+        .unwrap();
+    assert!(weights.iter().all(|v| *v >= 0.0));
+    assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    assert!(cost < equal_cost);
+    let (expected_return, risk) = objective(&weights);
+    assert!((portfolio.cost(&weights, expected_return, risk) - cost).abs() < 1e-12);
+}
@@ -0,0 +1,73 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Distribution {
+    /// Adds `strength/2*(x - mean)^2` per variable.
+    Gaussian,
+    /// Adds `strength*|x - mean|` per variable.
+    Laplace,
+}
+
+/// Soft prior added to the objective during a run, i.e. the negative log-density of
+/// the prior up to a constant.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Prior {
+    distribution: Distribution,
+    means: Vec<f64>,
+    strengths: Vec<f64>,
+}
+
+impl Prior {
+    pub fn new(distribution: Distribution, means: &[f64], strengths: &[f64]) -> Prior {
+        assert_eq!(means.len(), strengths.len());
+        assert!(strengths.iter().all(|v| *v >= 0.0));
+        Prior {distribution, means: means.to_vec(), strengths: strengths.to_vec()}
+    }
+
+    pub fn gaussian(means: &[f64], strengths: &[f64]) -> Prior {
+        Prior::new(Distribution::Gaussian, means, strengths)
+    }
+
+    pub fn laplace(means: &[f64], strengths: &[f64]) -> Prior {
+        Prior::new(Distribution::Laplace, means, strengths)
+    }
+
+    pub fn distribution(&self) -> Distribution {
+        self.distribution
+    }
+
+    pub fn means(&self) -> &[f64] {
+        &self.means
+    }
+
+    pub fn strengths(&self) -> &[f64] {
+        &self.strengths
+    }
+
+    pub fn len(&self) -> usize {
+        self.means.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.means.is_empty()
+    }
+
+    pub fn penalty(&self, x: &[f64]) -> f64 {
+        x.iter().zip(self.means.iter().zip(self.strengths.iter()))
+            .map(|(x, (mean, strength))| match self.distribution {
+                Distribution::Gaussian => 0.5*strength*(x - mean)*(x - mean),
+                Distribution::Laplace => strength*(x - mean).abs(),
+            })
+            .sum()
+    }
+}
+
+#[test]
+fn test_penalty_should_depend_on_distribution() {
+    let x = [1.0, -2.0];
+    assert_eq!(Prior::gaussian(&[0.0, 0.0], &[2.0, 1.0]).penalty(&x), 3.0);
+    assert_eq!(Prior::laplace(&[0.0, 0.0], &[2.0, 1.0]).penalty(&x), 4.0);
+}
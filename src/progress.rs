@@ -0,0 +1,40 @@
+/// Run state the wrapper can observe through objective calls alone.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    pub best_point: Vec<f64>,
+    pub best_value: f64,
+    /// Estimate of the trust region radius: the shortest trial step away from the
+    /// incumbent so far, starting from the initial radius.
+    pub rho: f64,
+    pub function_calls_count: usize,
+}
+
+impl Progress {
+    pub fn new(initial_trust_region_radius: f64) -> Progress {
+        Progress {
+            best_point: Vec::new(),
+            best_value: f64::INFINITY,
+            rho: initial_trust_region_radius,
+            function_calls_count: 0,
+        }
+    }
+
+    /// Returns whether the point became the incumbent.
+    pub fn update(&mut self, x: &[f64], value: f64) -> bool {
+        self.function_calls_count += 1;
+        if !self.best_point.is_empty() {
+            let step = x.iter().zip(self.best_point.iter()).map(|(a, b)| (a - b)*(a - b))
+                .sum::<f64>().sqrt();
+            if step > 0.0 {
+                self.rho = self.rho.min(step);
+            }
+        }
+        let improved = self.best_point.is_empty() || value < self.best_value;
+        if improved {
+            self.best_point.clear();
+            self.best_point.extend_from_slice(x);
+            self.best_value = value;
+        }
+        improved
+    }
+}
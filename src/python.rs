@@ -0,0 +1,59 @@
+//! Python extension module, built as a shared library with
+//! `cargo rustc --release --lib --features python --crate-type cdylib` or `maturin build
+//! --features python`. The library is then installed or renamed as `newuoa` with the
+//! extension suffix of the interpreter.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use fallible::FallibleError;
+use minimize::Options;
+
+/// `newuoa.minimize(function, x0, options=None)` for Python, running the same solver as
+/// `minimize::minimize`. `options` is a dict with the `minimize::Options` field names, the
+/// result a dict with `x`, `fun`, `nfev`, `rho` and `termination_reason`. An exception of
+/// `function` stops the run and is raised once the native routine returns.
+#[pyfunction]
+#[pyo3(signature = (function, x0, options=None))]
+fn minimize<'py>(py: Python<'py>, function: &Bound<'py, PyAny>, x0: Vec<f64>,
+        options: Option<&Bound<'py, PyDict>>) -> PyResult<Bound<'py, PyDict>> {
+    let options = match options {
+        Some(v) => Options {
+            number_of_interpolation_conditions: option(v, "number_of_interpolation_conditions")?,
+            initial_trust_region_radius: option(v, "initial_trust_region_radius")?,
+            final_trust_region_radius: option(v, "final_trust_region_radius")?,
+            max_function_calls_count: option(v, "max_function_calls_count")?,
+        },
+        None => Options::default(),
+    };
+    let mut values = x0;
+    let mut solver = options.solver(&values).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let result = solver.perform_fallible(&mut values, &mut |x: &[f64]| {
+        function.call1((x.to_vec(),)).and_then(|v| v.extract::<f64>())
+    });
+    let result = match result {
+        Ok(v) => v,
+        Err(FallibleError::Objective {error, ..}) => return Err(error),
+        Err(FallibleError::Solver(e)) => return Err(PyValueError::new_err(e.to_string())),
+    };
+    let dict = PyDict::new_bound(py);
+    dict.set_item("x", result.solution)?;
+    dict.set_item("fun", result.value)?;
+    dict.set_item("nfev", result.function_calls_count)?;
+    dict.set_item("rho", result.final_trust_region_radius)?;
+    dict.set_item("termination_reason", format!("{:?}", result.termination_reason))?;
+    Ok(dict)
+}
+
+fn option<'py, T: FromPyObject<'py>>(options: &Bound<'py, PyDict>, key: &str) -> PyResult<Option<T>> {
+    match options.get_item(key)? {
+        Some(v) if !v.is_none() => v.extract().map(Some),
+        _ => Ok(None),
+    }
+}
+
+#[pymodule]
+fn newuoa(module: &Bound<PyModule>) -> PyResult<()> {
+    module.add_function(pyo3::wrap_pyfunction!(minimize, module)?)
+}
@@ -0,0 +1,107 @@
+use linalg::solve;
+
+/// Quadratic `c + g'd + d'Hd/2` in the displacement `d = x - center`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuadraticModel {
+    center: Vec<f64>,
+    constant: f64,
+    gradient: Vec<f64>,
+    hessian: Vec<Vec<f64>>,
+}
+
+impl QuadraticModel {
+    /// Least-squares fit to sampled values. Needs at least `(n + 1)(n + 2)/2` points in
+    /// general position for a well-determined model; a tiny ridge term keeps the fit
+    /// defined otherwise.
+    pub fn fit(center: &[f64], points: &[Vec<f64>], values: &[f64]) -> Option<QuadraticModel> {
+        assert_eq!(points.len(), values.len());
+        let n = center.len();
+        let terms_count = (n + 1)*(n + 2)/2;
+        let mut normal = vec![vec![0.0; terms_count]; terms_count];
+        let mut rhs = vec![0.0; terms_count];
+        for (point, value) in points.iter().zip(values.iter()) {
+            let basis = QuadraticModel::basis(center, point);
+            for i in 0..terms_count {
+                rhs[i] += basis[i]*value;
+                for j in 0..terms_count {
+                    normal[i][j] += basis[i]*basis[j];
+                }
+            }
+        }
+        let trace = (0..terms_count).map(|i| normal[i][i]).sum::<f64>();
+        for (i, row) in normal.iter_mut().enumerate() {
+            row[i] += 1e-12*trace.max(1.0);
+        }
+        let coefficients = solve(&normal, &rhs)?;
+        let index = |i: usize, j: usize| n + 1 + i.max(j)*(i.max(j) + 1)/2 + i.min(j);
+        let hessian = (0..n).map(|i| (0..n).map(|j| coefficients[index(i, j)]).collect())
+            .collect();
+        let gradient = coefficients[1..n + 1].to_vec();
+        Some(QuadraticModel {center: center.to_vec(), constant: coefficients[0], gradient, hessian})
+    }
+
+    pub fn center(&self) -> &[f64] {
+        &self.center
+    }
+
+    pub fn constant(&self) -> f64 {
+        self.constant
+    }
+
+    /// Gradient at the center.
+    pub fn linear(&self) -> &[f64] {
+        &self.gradient
+    }
+
+    pub fn hessian(&self) -> &[Vec<f64>] {
+        &self.hessian
+    }
+
+    pub fn eval(&self, x: &[f64]) -> f64 {
+        let d = self.displacement(x);
+        let mut result = self.constant;
+        for i in 0..d.len() {
+            result += self.gradient[i]*d[i];
+            for j in 0..d.len() {
+                result += 0.5*d[i]*self.hessian[i][j]*d[j];
+            }
+        }
+        result
+    }
+
+    pub fn gradient(&self, x: &[f64]) -> Vec<f64> {
+        let d = self.displacement(x);
+        (0..d.len())
+            .map(|i| self.gradient[i] + (0..d.len()).map(|j| self.hessian[i][j]*d[j]).sum::<f64>())
+            .collect()
+    }
+
+    fn displacement(&self, x: &[f64]) -> Vec<f64> {
+        x.iter().zip(self.center.iter()).map(|(x, c)| x - c).collect()
+    }
+
+    fn basis(center: &[f64], point: &[f64]) -> Vec<f64> {
+        let d = point.iter().zip(center.iter()).map(|(x, c)| x - c).collect::<Vec<_>>();
+        let mut result = Vec::with_capacity((d.len() + 1)*(d.len() + 2)/2);
+        result.push(1.0);
+        result.extend_from_slice(&d);
+        for i in 0..d.len() {
+            for j in 0..i + 1 {
+                result.push(if i == j { 0.5*d[i]*d[i] } else { d[i]*d[j] });
+            }
+        }
+        result
+    }
+}
+
+#[test]
+fn test_fit_should_recover_exact_quadratic() {
+    let function = |x: &[f64]| 1.0 + 2.0*x[0] - x[1] + 1.5*x[0]*x[0] + 0.5*x[0]*x[1] + x[1]*x[1];
+    let points = (0..9).map(|k| vec![(k % 3) as f64 - 1.0, (k/3) as f64 - 1.0])
+        .collect::<Vec<_>>();
+    let values = points.iter().map(|p| function(p)).collect::<Vec<_>>();
+    let model = QuadraticModel::fit(&[0.0, 0.0], &points, &values).unwrap();
+    assert!((model.eval(&[0.3, -0.7]) - function(&[0.3, -0.7])).abs() < 1e-8);
+    assert!((model.hessian()[0][1] - 0.5).abs() < 1e-8);
+    assert!((model.gradient(&[1.0, 0.0])[0] - 5.0).abs() < 1e-8);
+}
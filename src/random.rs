@@ -0,0 +1,51 @@
+/// SplitMix64 generator: small, seedable and identical on every platform, which keeps
+/// seeded runs reproducible.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random {state: seed}
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64/(1u64 << 53) as f64
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_f64()*bound as f64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = self.below(i + 1);
+            values.swap(i, j);
+        }
+    }
+
+    /// Latin hypercube design of `count` points in the box: every coordinate takes one
+    /// value from each of `count` equal strata.
+    pub fn latin_hypercube(&mut self, lower: &[f64], upper: &[f64], count: usize) -> Vec<Vec<f64>> {
+        let mut result = vec![lower.to_vec(); count];
+        for (i, (l, u)) in lower.iter().zip(upper.iter()).enumerate() {
+            let mut strata = (0..count).collect::<Vec<_>>();
+            self.shuffle(&mut strata);
+            for (point, stratum) in result.iter_mut().zip(strata.iter()) {
+                let t = (*stratum as f64 + self.next_f64())/count as f64;
+                point[i] = l + (u - l)*t;
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,128 @@
+use Newuoa;
+use error::NewuoaError;
+use random::Random;
+use result::{OptimizationResult, TerminationReason};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestartsReport {
+    /// Best result over all runs, its function calls count is the one of its own run.
+    pub result: OptimizationResult,
+    pub restarts_count: usize,
+    pub function_calls_count: usize,
+}
+
+/// Restarts the solver from a seeded random perturbation of the best point each time a
+/// run converges before the total budget is exhausted. Coordinates are perturbed
+/// uniformly within `perturbation`, at most `max_restarts_count` times.
+pub struct Restarts {
+    total_function_calls_count: usize,
+    max_restarts_count: usize,
+    perturbation: f64,
+    seed: u64,
+}
+
+impl Default for Restarts {
+    fn default() -> Restarts {
+        Restarts::new()
+    }
+}
+
+impl Restarts {
+    pub fn new() -> Restarts {
+        Restarts {
+            total_function_calls_count: 10000,
+            max_restarts_count: usize::MAX,
+            perturbation: 1.0,
+            seed: 0,
+        }
+    }
+
+    pub fn total_function_calls_count(&mut self, value: usize) -> &mut Self {
+        self.total_function_calls_count = value;
+        self
+    }
+
+    pub fn max_restarts_count(&mut self, value: usize) -> &mut Self {
+        self.max_restarts_count = value;
+        self
+    }
+
+    pub fn perturbation(&mut self, value: f64) -> &mut Self {
+        assert!(value > 0.0);
+        self.perturbation = value;
+        self
+    }
+
+    pub fn seed(&mut self, value: u64) -> &mut Self {
+        self.seed = value;
+        self
+    }
+
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [f64], function: &mut F)
+            -> Result<RestartsReport, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let npt = solver.settings().number_of_interpolation_conditions;
+        let mut random = Random::new(self.seed);
+        let mut remaining = self.total_function_calls_count;
+        let mut restarts_count = 0;
+        let mut start = values.to_vec();
+        let mut result = solver
+            .max_function_calls_count(remaining.max(1))
+            .perform_mut(&mut start, function)?;
+        remaining -= result.function_calls_count.min(remaining);
+        let mut converged = result.termination_reason == TerminationReason::Converged;
+        while converged && remaining > npt && restarts_count < self.max_restarts_count {
+            restarts_count += 1;
+            #[cfg(feature = "log")]
+            log::info!("newuoa restart {} from f = {} with {} evals left", restarts_count, result.value,
+                remaining);
+            for (v, best) in start.iter_mut().zip(result.solution.iter()) {
+                *v = best + self.perturbation*(2.0*random.next_f64() - 1.0);
+            }
+            let restarted = solver
+                .max_function_calls_count(remaining)
+                .perform_mut(&mut start, function)?;
+            remaining -= restarted.function_calls_count.min(remaining);
+            converged = restarted.termination_reason == TerminationReason::Converged;
+            if restarted.value < result.value {
+                result = restarted;
+            }
+        }
+        values[..result.solution.len()].copy_from_slice(&result.solution);
+        Ok(RestartsReport {
+            result,
+            restarts_count,
+            function_calls_count: self.total_function_calls_count - remaining,
+        })
+    }
+}
+
+#[test]
+fn test_restarts_should_be_reproducible() {
+    let run = || {
+        let mut values = [2.0, 2.0];
+        Restarts::new()
+            .total_function_calls_count(200)
+            .seed(3)
+            .perform_mut(Newuoa::new().final_trust_region_radius(1e-2), &mut values,
+                &mut |x: &[f64]| (x[0]*x[0] - 1.0).powi(2) + (x[1]*x[1] - 1.0).powi(2))
+            .unwrap()
+    };
+    let first = run();
+    let second = run();
+    assert_eq!(first.result.solution, second.result.solution);
+    assert_eq!(first.restarts_count, second.restarts_count);
+    assert!(first.function_calls_count <= 200);
+}
+
+#[test]
+fn test_restarts_should_stop_at_max_restarts_count() {
+    let mut values = [2.0, 2.0];
+    let report = Restarts::new()
+        .total_function_calls_count(10000)
+        .max_restarts_count(1)
+        .perform_mut(Newuoa::new().final_trust_region_radius(1e-2), &mut values,
+            &mut |x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(report.restarts_count, 1);
+}
@@ -0,0 +1,247 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use Newuoa;
+use compression;
+use error::NewuoaError;
+use linalg::{gradient, hessian};
+use metadata::RunMetadata;
+use quadratic::QuadraticModel;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TerminationReason {
+    /// The trust region radius reached the final radius.
+    Converged,
+    MaxFunctionCallsReached,
+    /// An observer returned `ControlFlow::Break`.
+    StoppedByUser,
+    /// `Newuoa::max_duration` elapsed.
+    TimeLimit,
+    /// An evaluation reached `Newuoa::stop_at_objective`.
+    TargetReached,
+    /// An improvement of the best value was within `Newuoa::ftol_rel` or `Newuoa::ftol_abs`.
+    FunctionToleranceReached,
+    /// The objective returned a non-finite value under `NonFinitePolicy::Abort`.
+    NonFiniteObjective,
+    /// The objective of `Newuoa::perform_fallible` returned an error.
+    ObjectiveFailed,
+}
+
+/// Objective call recorded with `Newuoa::record_history`, the value including any prior.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Evaluation {
+    pub point: Vec<f64>,
+    pub value: f64,
+}
+
+/// Change of the radius estimate recorded with `Newuoa::record_history`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RadiusChange {
+    /// Evaluations made so far, the first entry is the initial radius at the first one.
+    pub function_calls_count: usize,
+    pub rho: f64,
+    pub best_value: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OptimizationResult {
+    pub value: f64,
+    pub solution: Vec<f64>,
+    /// Calls of the user objective, counted by the wrapper.
+    pub function_calls_count: usize,
+    /// Final radius when converged, otherwise the estimate the run had reached.
+    pub final_trust_region_radius: f64,
+    pub termination_reason: TerminationReason,
+    pub metadata: RunMetadata,
+    /// Every evaluation in call order, empty unless `Newuoa::record_history` is set.
+    pub history: Vec<Evaluation>,
+    /// Trajectory of the radius estimate, empty unless `Newuoa::record_history` is set. A
+    /// run still shrinking it when the budget ran out had not reached the final radius.
+    pub rho_history: Vec<RadiusChange>,
+    /// First failure to write a `Newuoa::checkpoint_every` file, the run goes on regardless.
+    pub checkpoint_error: Option<String>,
+    /// Calls answered by `Cached::perform_mut` without evaluating the objective.
+    pub cache_hits: usize,
+    /// Time spent in the objective, measured by an `Instrumented` wrapper of it.
+    pub objective_duration: Duration,
+}
+
+impl OptimizationResult {
+    /// Runs `solver` starting from this solution, typically configured with smaller radii
+    /// to polish it. Function calls, objective duration and the recorded history are
+    /// accumulated across both runs.
+    pub fn continue_with<F>(&self, solver: &mut Newuoa, function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let mut values = self.solution.clone();
+        let mut result = solver.perform_mut(&mut values, function)?;
+        result.function_calls_count += self.function_calls_count;
+        result.objective_duration += self.objective_duration;
+        if !result.history.is_empty() {
+            let mut history = self.history.clone();
+            history.append(&mut result.history);
+            result.history = history;
+            let mut rho_history = self.rho_history.clone();
+            rho_history.extend(result.rho_history.iter().map(|v| RadiusChange {
+                function_calls_count: v.function_calls_count + self.function_calls_count,
+                ..*v
+            }));
+            result.rho_history = rho_history;
+        }
+        Ok(result)
+    }
+
+    /// Writes `history` as CSV, a `value,x0,x1,...` header then one line per evaluation
+    /// with every float in its shortest exact form.
+    pub fn write_history<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
+        write!(writer, "value")?;
+        for i in 0..self.solution.len() {
+            write!(writer, ",x{}", i)?;
+        }
+        writeln!(writer)?;
+        for evaluation in self.history.iter() {
+            write!(writer, "{}", evaluation.value)?;
+            for v in evaluation.point.iter() {
+                write!(writer, ",{}", v)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// `write_history` to `path`, zstd compressed when it ends with `.zst`.
+    pub fn save_history<P>(&self, path: P) -> io::Result<()> where P: AsRef<Path> {
+        let path = path.as_ref();
+        let mut bytes = Vec::new();
+        self.write_history(&mut bytes)?;
+        File::create(path)?.write_all(&compression::for_path(path, &bytes)?)
+    }
+
+    /// Central differences of `function` at the solution with steps of `step` times the
+    /// magnitude of each coordinate, at least `step`, costing `2n` calls. The native
+    /// routine does not expose its model, so the gradient is estimated afresh.
+    pub fn approx_gradient<F>(&self, function: &F, step: f64) -> Vec<f64>
+            where F: Fn(&[f64]) -> f64 {
+        gradient(function, &self.solution, step)
+    }
+
+    /// Finite differences of `function` at the solution like `approx_gradient`, costing
+    /// `2n^2 + 1` calls.
+    pub fn approx_hessian<F>(&self, function: &F, step: f64) -> Vec<Vec<f64>>
+            where F: Fn(&[f64]) -> f64 {
+        hessian(function, &self.solution, step)
+    }
+
+    /// Quadratic fitted around the solution to the recorded evaluations closest to it,
+    /// twice as many as the model has coefficients, without calling the objective again.
+    /// `None` unless `Newuoa::record_history` kept at least `(n + 1)(n + 2)/2` of them.
+    pub fn quadratic_model(&self) -> Option<QuadraticModel> {
+        let n = self.solution.len();
+        let terms_count = (n + 1)*(n + 2)/2;
+        if self.history.len() < terms_count {
+            return None;
+        }
+        let distance = |v: &Evaluation| {
+            v.point.iter().zip(self.solution.iter()).map(|(x, c)| (x - c)*(x - c)).sum::<f64>()
+        };
+        let mut nearest = self.history.iter().collect::<Vec<_>>();
+        nearest.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        nearest.truncate(2*terms_count);
+        let points = nearest.iter().map(|v| v.point.clone()).collect::<Vec<_>>();
+        let values = nearest.iter().map(|v| v.value).collect::<Vec<_>>();
+        QuadraticModel::fit(&self.solution, &points, &values)
+    }
+}
+
+/// Reads a history written by `save_history`, compressed or not.
+pub fn load_history<P>(path: P) -> io::Result<Vec<Evaluation>> where P: AsRef<Path> {
+    let bytes = fs::read(path)?;
+    let bytes = compression::decompress(&bytes)?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid history line");
+    let text = ::std::str::from_utf8(&bytes).map_err(|_| invalid())?;
+    text.lines()
+        .skip(1)
+        .map(|line| {
+            let mut values = line.split(',').map(|v| v.parse::<f64>().map_err(|_| invalid()));
+            let value = values.next().ok_or_else(invalid)??;
+            Ok(Evaluation {point: values.collect::<io::Result<_>>()?, value})
+        })
+        .collect()
+}
+
+#[test]
+fn test_approx_gradient_should_differentiate_at_solution() {
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(10);
+    let function = |x: &[f64]| 3.0*x[0] + x[1]*x[1];
+    let result = solver.perform(&mut [1.0, 2.0], &function).unwrap();
+    let gradient = result.approx_gradient(&function, 1e-5);
+    assert!((gradient[0] - 3.0).abs() < 1e-6);
+    assert!((gradient[1] - 2.0*result.solution[1]).abs() < 1e-6);
+}
+
+#[test]
+fn test_approx_hessian_should_differentiate_at_solution() {
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(10);
+    let function = |x: &[f64]| x[0]*x[0] + 3.0*x[0]*x[1];
+    let result = solver.perform(&mut [1.0, 2.0], &function).unwrap();
+    let hessian = result.approx_hessian(&function, 1e-4);
+    assert!((hessian[0][0] - 2.0).abs() < 1e-4);
+    assert!((hessian[0][1] - 3.0).abs() < 1e-4);
+    assert!(hessian[1][1].abs() < 1e-4);
+}
+
+#[test]
+fn test_quadratic_model_should_fit_recorded_evaluations() {
+    let function = |x: &[f64]| 1.0 + x[0] - 2.0*x[1] + x[0]*x[0] + 0.5*x[0]*x[1] + 2.0*x[1]*x[1];
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(40);
+    let mut result = solver.perform(&mut [1.0, 2.0], &function).unwrap();
+    assert!(result.quadratic_model().is_none());
+    result.history = (0..9)
+        .map(|k| {
+            let point = vec![result.solution[0] + (k % 3) as f64 - 1.0, result.solution[1] + (k/3) as f64 - 1.0];
+            Evaluation {value: function(&point), point}
+        })
+        .collect();
+    let model = result.quadratic_model().unwrap();
+    assert!((model.eval(&[0.3, 0.1]) - function(&[0.3, 0.1])).abs() < 1e-6);
+    assert!((model.hessian()[0][1] - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn test_save_history_should_round_trip() {
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(20).record_history(true);
+    let result = solver.perform(&mut [0.1, 2.0/3.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]).unwrap();
+    let mut paths = vec![::std::env::temp_dir().join(format!("newuoa-history-{}.csv", ::std::process::id()))];
+    if cfg!(feature = "zstd") {
+        paths.push(paths[0].with_extension("csv.zst"));
+    }
+    for path in paths.iter() {
+        result.save_history(path).unwrap();
+        let history = load_history(path);
+        fs::remove_file(path).unwrap();
+        assert_eq!(history.unwrap(), result.history);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_optimization_result_should_round_trip_through_json() {
+    let mut solver = Newuoa::new();
+    solver.max_function_calls_count(20).record_history(true);
+    let result = solver.perform(&mut [1.0, 2.0], &|x: &[f64]| x[0]*x[0] + x[1]*x[1]).unwrap();
+    let json = ::serde_json::to_string(&result).unwrap();
+    assert_eq!(::serde_json::from_str::<OptimizationResult>(&json).unwrap(), result);
+}
@@ -0,0 +1,295 @@
+use std::any::Any;
+use std::fmt;
+use std::io::{self, Write};
+use std::mem::take;
+use std::ops::ControlFlow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use checkpoint::Checkpoint;
+use configuration::Configuration;
+use metadata::{RunId, Settings};
+use observer::{Diagnostics, IterationInfo, Observer};
+use prior::Prior;
+use progress::Progress;
+use result::{Evaluation, RadiusChange, TerminationReason};
+use types::{NonFinitePolicy, Verbosity};
+
+/// Settings handled by `Run` rather than the native routine: mostly stopping rules checked
+/// after every evaluation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RunOptions {
+    pub max_duration: Option<Duration>,
+    pub stop_at_objective: Option<f64>,
+    pub ftol_rel: Option<f64>,
+    pub ftol_abs: Option<f64>,
+    pub non_finite_policy: NonFinitePolicy,
+    pub record_history: bool,
+    pub verbosity: Verbosity,
+}
+
+/// Maps the points of the native routine to the ones of the objective: fixed variables
+/// are left out of the native routine and the free ones are divided by their scaling.
+#[derive(Clone, Debug)]
+pub struct Space<'a> {
+    pub variables_count: usize,
+    pub scaling: Option<&'a [f64]>,
+    /// Sorted by index.
+    pub fixed_variables: &'a [(usize, f64)],
+}
+
+impl<'a> Space<'a> {
+    pub fn is_identity(&self) -> bool {
+        self.scaling.is_none() && self.fixed_variables.is_empty()
+    }
+
+    pub fn to_internal(&self, values: &[f64]) -> Vec<f64> {
+        (0..self.variables_count)
+            .filter(|i| self.fixed_variables.iter().all(|v| v.0 != *i))
+            .map(|i| values[i]/self.scale(i))
+            .collect()
+    }
+
+    pub fn to_external(&self, x: &[f64], result: &mut Vec<f64>) {
+        result.clear();
+        let mut free = x.iter();
+        let mut fixed = self.fixed_variables.iter().peekable();
+        for i in 0..self.variables_count {
+            match fixed.peek() {
+                Some(&&(index, value)) if index == i => {
+                    fixed.next();
+                    result.push(value);
+                }
+                _ => result.push(free.next().expect("too few free variables")*self.scale(i)),
+            }
+        }
+    }
+
+    fn scale(&self, index: usize) -> f64 {
+        self.scaling.map_or(1.0, |v| v[index])
+    }
+}
+
+/// State of a single `perform_mut` call shared by every objective evaluation. Once a stop
+/// is requested the user objective is not called anymore: the native routine is fed the
+/// best value so far until it returns, which it does without improving the incumbent. A
+/// panic of the objective or the observer is caught so it never unwinds into the native
+/// code, stops the run the same way and is kept to be resumed by the caller. Points of the
+/// native routine are mapped through `Space`, everything else sees the objective's points.
+/// Values are minimized times `sign`, which is `-1` when maximizing, and everything but the
+/// native routine and the progress sees them with the sign of the objective.
+pub struct Run<'a> {
+    pub progress: Progress,
+    pub stop: Option<TerminationReason>,
+    pub history: Option<Vec<Evaluation>>,
+    pub rho_history: Option<Vec<RadiusChange>>,
+    pub panic: Option<Box<dyn Any + Send>>,
+    pub checkpoint_error: Option<String>,
+    pub diagnostics: Option<&'a mut Diagnostics>,
+    pub sign: f64,
+    /// Best and worst finite values of the objective so far.
+    finite_range: Option<(f64, f64)>,
+    settings: Settings,
+    options: RunOptions,
+    started_at: Instant,
+    run_id: RunId,
+    pub space: Space<'a>,
+    point: Vec<f64>,
+    prior: Option<&'a Prior>,
+    observer: Option<&'a mut Observer>,
+    /// Period, path and the configuration saved with every checkpoint.
+    checkpoint: Option<(usize, &'a Path, Configuration)>,
+}
+
+impl<'a> Run<'a> {
+    pub fn new(settings: Settings, options: RunOptions, run_id: RunId, space: Space<'a>,
+            prior: Option<&'a Prior>, observer: Option<&'a mut Observer>,
+            checkpoint: Option<(usize, &'a Path, Configuration)>) -> Run<'a> {
+        Run {
+            progress: Progress::new(settings.initial_trust_region_radius),
+            stop: None,
+            history: if options.record_history { Some(Vec::new()) } else { None },
+            rho_history: if options.record_history { Some(Vec::new()) } else { None },
+            panic: None,
+            checkpoint_error: None,
+            diagnostics: None,
+            sign: 1.0,
+            finite_range: None,
+            settings,
+            options,
+            started_at: Instant::now(),
+            run_id,
+            space,
+            point: Vec::new(),
+            prior,
+            observer,
+            checkpoint,
+        }
+    }
+
+    pub fn evaluate<F>(&mut self, function: &mut F, x: &[f64]) -> f64
+            where F: FnMut(&[f64]) -> ControlFlow<(), f64> {
+        if self.stop.is_some() || self.panic.is_some() {
+            return self.fallback_value();
+        }
+        let mut point = take(&mut self.point);
+        let x = if self.space.is_identity() {
+            x
+        } else {
+            self.space.to_external(x, &mut point);
+            &point[..]
+        };
+        let result = match catch_unwind(AssertUnwindSafe(|| self.evaluate_unchecked(function, x))) {
+            Ok(value) => value,
+            Err(payload) => {
+                self.panic = Some(payload);
+                self.fallback_value()
+            }
+        };
+        self.point = point;
+        result
+    }
+
+    fn evaluate_unchecked<F>(&mut self, function: &mut F, x: &[f64]) -> f64
+            where F: FnMut(&[f64]) -> ControlFlow<(), f64> {
+        let value = match function(x) {
+            ControlFlow::Continue(value) => value,
+            ControlFlow::Break(()) => {
+                self.progress.function_calls_count += 1;
+                self.stop = Some(TerminationReason::ObjectiveFailed);
+                return self.fallback_value();
+            }
+        };
+        let mut value = self.sign*value + self.prior.map_or(0.0, |p| p.penalty(x));
+        if let Some(ref mut history) = self.history {
+            history.push(Evaluation {point: x.to_vec(), value});
+        }
+        if value.is_finite() {
+            self.finite_range = Some(self.finite_range.map_or((value, value), |(best, worst)| {
+                (best.min(value), worst.max(value))
+            }));
+        } else {
+            match self.options.non_finite_policy {
+                NonFinitePolicy::WorstValue => {
+                    value = self.finite_range.map_or(0.0, |(best, worst)| worst + (worst - best).max(1.0));
+                }
+                NonFinitePolicy::Penalty(penalty) => value = penalty,
+                NonFinitePolicy::Abort => {
+                    self.progress.function_calls_count += 1;
+                    self.stop = Some(TerminationReason::NonFiniteObjective);
+                    return self.fallback_value();
+                }
+            }
+        }
+        let previous_best_value = self.progress.best_value;
+        let previous_rho = self.progress.rho;
+        let improvement = if self.progress.update(x, value) && previous_best_value.is_finite() {
+            Some(previous_best_value - value)
+        } else {
+            None
+        };
+        let rho_changed = self.progress.function_calls_count == 1 || self.progress.rho != previous_rho;
+        if let Some(ref mut rho_history) = self.rho_history {
+            if rho_changed {
+                rho_history.push(RadiusChange {
+                    function_calls_count: self.progress.function_calls_count,
+                    rho: self.progress.rho,
+                    best_value: self.progress.best_value,
+                });
+            }
+        }
+        let sign = self.sign;
+        #[cfg(feature = "log")]
+        if rho_changed {
+            log::debug!("newuoa run {}: rho = {} at eval {}, best f = {}", self.run_id, self.progress.rho,
+                self.progress.function_calls_count, sign*self.progress.best_value);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(eval = self.progress.function_calls_count, f = sign*value, rho = self.progress.rho,
+            best_f = sign*self.progress.best_value);
+        if self.options.verbosity == Verbosity::PerEval
+                || self.options.verbosity == Verbosity::PerRho && rho_changed {
+            let progress = &self.progress;
+            report(&mut self.diagnostics, format_args!("newuoa: eval {}: f = {}, best f = {}, rho = {}",
+                progress.function_calls_count, sign*value, sign*progress.best_value, progress.rho));
+        }
+        if let Some((function_calls_count, path, _)) = self.checkpoint {
+            if self.progress.function_calls_count.is_multiple_of(function_calls_count) {
+                self.save_checkpoint(path);
+            }
+        }
+        if let Some(ref mut observer) = self.observer {
+            let progress = &self.progress;
+            let control = observer(&IterationInfo {
+                iteration: progress.function_calls_count
+                    .saturating_sub(self.settings.number_of_interpolation_conditions),
+                function_calls_count: progress.function_calls_count,
+                point: x,
+                value: sign*value,
+                best_point: &progress.best_point,
+                best_value: sign*progress.best_value,
+                rho: progress.rho,
+                run_id: self.run_id,
+            });
+            if let ControlFlow::Break(()) = control {
+                self.stop = Some(TerminationReason::StoppedByUser);
+            }
+        }
+        if self.stop.is_none() {
+            self.stop = self.check(improvement);
+        }
+        value
+    }
+
+    /// Keeps the first error, later checkpoints are still attempted.
+    fn save_checkpoint(&mut self, path: &Path) {
+        let configuration = match self.checkpoint {
+            Some((_, _, ref configuration)) => configuration.clone(),
+            None => return,
+        };
+        let checkpoint = Checkpoint {
+            configuration,
+            point: self.progress.best_point.clone(),
+            value: self.sign*self.progress.best_value,
+            function_calls_count: self.progress.function_calls_count,
+        };
+        if let Err(error) = checkpoint.replace(path) {
+            if self.checkpoint_error.is_none() {
+                self.checkpoint_error = Some(format!("{}: {}", path.display(), error));
+            }
+        }
+    }
+
+    /// Value fed to the native routine instead of calling the objective. Being constant
+    /// it flattens the model, so the routine returns quickly without overflowing.
+    fn fallback_value(&self) -> f64 {
+        if self.progress.best_point.is_empty() { 0.0 } else { self.progress.best_value }
+    }
+
+    fn check(&self, improvement: Option<f64>) -> Option<TerminationReason> {
+        if self.options.stop_at_objective.is_some_and(|v| self.progress.best_value <= self.sign*v) {
+            return Some(TerminationReason::TargetReached);
+        }
+        if let Some(improvement) = improvement {
+            let best_value = self.progress.best_value;
+            if self.options.ftol_abs.is_some_and(|v| improvement <= v)
+                    || self.options.ftol_rel.is_some_and(|v| improvement <= v*best_value.abs()) {
+                return Some(TerminationReason::FunctionToleranceReached);
+            }
+        }
+        if self.options.max_duration.is_some_and(|v| self.started_at.elapsed() >= v) {
+            return Some(TerminationReason::TimeLimit);
+        }
+        None
+    }
+}
+
+/// Writes a line to `diagnostics` or to stderr without one. Write errors are ignored, a
+/// report never fails a run.
+pub fn report(diagnostics: &mut Option<&mut Diagnostics>, message: fmt::Arguments) {
+    let _ = match *diagnostics {
+        Some(ref mut writer) => writeln!(writer, "{}", message),
+        None => writeln!(io::stderr(), "{}", message),
+    };
+}
@@ -0,0 +1,60 @@
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+/// Floating point type of the objective. The native routine works in `f64`, so points are
+/// converted on every evaluation and single precision only saves memory on the user side.
+pub trait Scalar: Copy {
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+impl Scalar for f32 {
+    fn from_f64(value: f64) -> f32 {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl Scalar for f64 {
+    fn from_f64(value: f64) -> f64 {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Newuoa {
+    /// `perform_mut` for objectives over any `Scalar`, the solution is rounded back into
+    /// `values`. The result keeps the `f64` values seen by the native routine.
+    pub fn perform_scalar<T, F>(&mut self, values: &mut [T], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where T: Scalar, F: FnMut(&[T]) -> T {
+        let mut internal = values.iter().map(|v| v.to_f64()).collect::<Vec<_>>();
+        let mut point = Vec::with_capacity(values.len());
+        let result = self.perform_mut(&mut internal, &mut |x: &[f64]| {
+            point.clear();
+            point.extend(x.iter().map(|v| T::from_f64(*v)));
+            function(&point).to_f64()
+        });
+        for (value, v) in values.iter_mut().zip(internal) {
+            *value = T::from_f64(v);
+        }
+        result
+    }
+}
+
+#[test]
+fn test_perform_scalar_with_f32_should_write_solution_back() {
+    let mut values = [1.0f32, 1.0];
+    let result = Newuoa::new()
+        .max_function_calls_count(30)
+        .perform_scalar(&mut values, &mut |x: &[f32]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(values, [result.solution[0] as f32, result.solution[1] as f32]);
+}
@@ -0,0 +1,175 @@
+use Newuoa;
+use error::NewuoaError;
+use quadratic::QuadraticModel;
+use random::Random;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreeningReport {
+    /// Estimated variation of the objective over the screening box caused by each
+    /// variable, relative to the most important one.
+    pub importance: Vec<f64>,
+    /// Variables with importance below the threshold, suggested for freezing.
+    pub negligible: Vec<usize>,
+    pub model: Option<QuadraticModel>,
+    pub function_calls_count: usize,
+}
+
+/// Pre-phase evaluating a Latin hypercube design in a box of half-width `radius` around
+/// the start point and fitting a quadratic model to it to rank variables by importance.
+pub struct Screening {
+    radius: f64,
+    samples_count: Option<usize>,
+    threshold: f64,
+    seed: u64,
+}
+
+impl Default for Screening {
+    fn default() -> Screening {
+        Screening::new()
+    }
+}
+
+impl Screening {
+    pub fn new() -> Screening {
+        Screening {
+            radius: 1.0,
+            samples_count: None,
+            threshold: 1e-3,
+            seed: 0,
+        }
+    }
+
+    pub fn radius(&mut self, value: f64) -> &mut Self {
+        assert!(value > 0.0);
+        self.radius = value;
+        self
+    }
+
+    /// Defaults to the number of quadratic model coefficients plus the dimension.
+    pub fn samples_count(&mut self, value: usize) -> &mut Self {
+        self.samples_count = Some(value);
+        self
+    }
+
+    pub fn threshold(&mut self, value: f64) -> &mut Self {
+        assert!((0.0..=1.0).contains(&value));
+        self.threshold = value;
+        self
+    }
+
+    pub fn seed(&mut self, value: u64) -> &mut Self {
+        self.seed = value;
+        self
+    }
+
+    pub fn screen<F>(&self, values: &[f64], function: &mut F) -> ScreeningReport
+            where F: FnMut(&[f64]) -> f64 {
+        let n = values.len();
+        let samples_count = self.samples_count.unwrap_or((n + 1)*(n + 2)/2 + n);
+        let points = self.design(values, samples_count);
+        let evaluated = points.iter().map(|p| function(p)).collect::<Vec<_>>();
+        let model = QuadraticModel::fit(values, &points, &evaluated);
+        let importance = match model {
+            Some(ref model) => self.importance(model),
+            None => vec![1.0; n],
+        };
+        let negligible = (0..n).filter(|&i| importance[i] < self.threshold).collect();
+        ScreeningReport {importance, negligible, model, function_calls_count: samples_count}
+    }
+
+    /// Screens the variables the solver does not already hold, then runs it over the ones
+    /// that are not negligible while holding the others at their start values. At least
+    /// two variables are kept free. The variables fixed by the caller are screened at
+    /// their fixed values, reported with zero importance and held again afterwards, and
+    /// the model is over the free variables.
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [f64], function: &mut F)
+            -> Result<(ScreeningReport, f64), NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        let n = values.len();
+        let fixed_variables = solver.fixed_variables.clone();
+        let number_of_interpolation_conditions = solver.number_of_interpolation_conditions;
+        let free = (0..n).filter(|i| fixed_variables.iter().all(|v| v.0 != *i)).collect::<Vec<_>>();
+        let mut report = {
+            let mut point = values.to_vec();
+            for &(i, value) in fixed_variables.iter().filter(|v| v.0 < n) {
+                point[i] = value;
+            }
+            let start = free.iter().map(|&i| values[i]).collect::<Vec<_>>();
+            self.screen(&start, &mut |x: &[f64]| {
+                for (&i, v) in free.iter().zip(x.iter()) {
+                    point[i] = *v;
+                }
+                function(&point)
+            })
+        };
+        let mut order = (0..free.len()).collect::<Vec<_>>();
+        order.sort_by(|&l, &r| report.importance[r].total_cmp(&report.importance[l]));
+        let fixed = order.iter().cloned()
+            .enumerate()
+            .filter(|&(rank, k)| rank >= 2 && report.negligible.contains(&k))
+            .map(|(_, k)| free[k])
+            .collect::<Vec<_>>();
+        let mut importance = vec![0.0; n];
+        for (&i, v) in free.iter().zip(report.importance.iter()) {
+            importance[i] = *v;
+        }
+        report.importance = importance;
+        report.negligible = report.negligible.iter().map(|&k| free[k]).collect();
+        solver
+            .variables_count(n)
+            .number_of_interpolation_conditions(2*(free.len() - fixed.len()) + 1);
+        for &i in fixed.iter() {
+            solver.fix_variable(i, values[i]);
+        }
+        let result = solver.perform_mut(values, function);
+        solver.fixed_variables = fixed_variables;
+        solver.number_of_interpolation_conditions = number_of_interpolation_conditions;
+        Ok((report, result?.value))
+    }
+
+    fn design(&self, center: &[f64], samples_count: usize) -> Vec<Vec<f64>> {
+        let lower = center.iter().map(|v| v - self.radius).collect::<Vec<_>>();
+        let upper = center.iter().map(|v| v + self.radius).collect::<Vec<_>>();
+        Random::new(self.seed).latin_hypercube(&lower, &upper, samples_count)
+    }
+
+    fn importance(&self, model: &QuadraticModel) -> Vec<f64> {
+        let r = self.radius;
+        let hessian = model.hessian();
+        let variation = model.linear().iter().enumerate()
+            .map(|(i, g)| {
+                g.abs()*r + 0.5*r*r*hessian[i].iter().map(|h| h.abs()).sum::<f64>()
+            })
+            .collect::<Vec<_>>();
+        let max = variation.iter().cloned().fold(0.0, f64::max);
+        variation.iter().map(|v| if max > 0.0 { v/max } else { 0.0 }).collect()
+    }
+}
+
+#[test]
+fn test_screen_should_mark_unused_variable_negligible() {
+    let mut function = |x: &[f64]| (x[0] - 1.0).powi(2) + 5.0*x[1] + 0.0*x[2];
+    let report = Screening::new().seed(7).screen(&[0.0, 0.0, 0.0], &mut function);
+    assert_eq!(report.negligible, vec![2]);
+    assert_eq!(report.importance[1], 1.0);
+}
+
+#[test]
+fn test_perform_mut_should_keep_variables_fixed_by_caller() {
+    let mut solver = Newuoa::new();
+    solver.fix_variable(0, 2.0).max_function_calls_count(100);
+    let mut values = [0.0, 0.5, 0.5, 0.0];
+    let (report, value) = Screening::new()
+        .seed(3)
+        .perform_mut(&mut solver, &mut values, &mut |x: &[f64]| {
+            assert_eq!(x[0], 2.0);
+            x[0]*x[0] + (x[1] - 1.0).powi(2) + (x[2] + 1.0).powi(2) + 0.0*x[3]
+        })
+        .unwrap();
+    assert_eq!(report.importance[0], 0.0);
+    assert_eq!(report.negligible, vec![3]);
+    assert_eq!(values[0], 2.0);
+    assert!(value < 6.0);
+    assert_eq!(solver.configuration().fixed_variables, vec![(0, 2.0)]);
+    assert_eq!(solver.settings().number_of_interpolation_conditions, 4);
+}
@@ -0,0 +1,126 @@
+use Newuoa;
+use error::NewuoaError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decision {
+    pub stage: usize,
+    pub incumbent_score: f64,
+    pub challenger_score: f64,
+    pub promoted: bool,
+}
+
+/// Tunes a live system in short stages. Each stage runs the solver from the previous
+/// challenger, the incumbent at first, then scores both the incumbent and the stage result
+/// (the new challenger) with the holdout closure. The challenger replaces the incumbent only when its holdout
+/// score is lower by more than the margin.
+pub struct SequentialTuner {
+    stages_count: usize,
+    stage_function_calls_count: usize,
+    margin: f64,
+}
+
+impl Default for SequentialTuner {
+    fn default() -> SequentialTuner {
+        SequentialTuner::new()
+    }
+}
+
+impl SequentialTuner {
+    pub fn new() -> SequentialTuner {
+        SequentialTuner {
+            stages_count: 10,
+            stage_function_calls_count: 50,
+            margin: 0.0,
+        }
+    }
+
+    pub fn stages_count(&mut self, value: usize) -> &mut Self {
+        self.stages_count = value;
+        self
+    }
+
+    pub fn stage_function_calls_count(&mut self, value: usize) -> &mut Self {
+        self.stage_function_calls_count = value;
+        self
+    }
+
+    pub fn margin(&mut self, value: f64) -> &mut Self {
+        assert!(value >= 0.0);
+        self.margin = value;
+        self
+    }
+
+    pub fn run<F, H>(&self, solver: &mut Newuoa, values: &mut [f64], objective: &mut F,
+            holdout: &mut H) -> Result<Vec<Decision>, NewuoaError>
+            where F: FnMut(&[f64]) -> f64, H: FnMut(&[f64]) -> f64 {
+        let n = values.len();
+        solver
+            .variables_count(n)
+            .number_of_interpolation_conditions(2*n + 1)
+            .max_function_calls_count(self.stage_function_calls_count);
+        let mut history = Vec::with_capacity(self.stages_count);
+        let mut challenger = values.to_vec();
+        for stage in 0..self.stages_count {
+            solver.perform_mut(&mut challenger, objective)?;
+            let incumbent_score = holdout(values);
+            let challenger_score = holdout(&challenger);
+            let promoted = challenger_score + self.margin < incumbent_score;
+            if promoted {
+                values.copy_from_slice(&challenger);
+            }
+            history.push(Decision {stage, incumbent_score, challenger_score, promoted});
+        }
+        Ok(history)
+    }
+}
+
+#[test]
+fn test_run_should_promote_challengers_winning_on_holdout() {
+    let mut values = [1.0, 1.0];
+    let mut function = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    let mut holdout = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    let decisions = SequentialTuner::new()
+        .stages_count(3)
+        .stage_function_calls_count(20)
+        .run(&mut Newuoa::new(), &mut values, &mut function, &mut holdout)
+        .unwrap();
+    assert_eq!(decisions.len(), 3);
+    assert!(decisions[0].promoted);
+    assert_eq!(decisions[0].incumbent_score, 2.0);
+    for (i, pair) in decisions.windows(2).enumerate() {
+        assert_eq!(pair[1].stage, i + 1);
+        let incumbent_score = if pair[0].promoted { pair[0].challenger_score } else { pair[0].incumbent_score };
+        assert_eq!(pair[1].incumbent_score, incumbent_score);
+    }
+    assert_eq!(holdout(&values), decisions[2].challenger_score.min(decisions[2].incumbent_score));
+}
+
+#[test]
+fn test_run_should_keep_incumbent_within_margin() {
+    let mut values = [1.0, 1.0];
+    let decisions = SequentialTuner::new()
+        .stages_count(2)
+        .stage_function_calls_count(20)
+        .margin(10.0)
+        .run(&mut Newuoa::new(), &mut values, &mut |x: &[f64]| x[0]*x[0] + x[1]*x[1],
+            &mut |x: &[f64]| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert!(decisions.iter().all(|v| !v.promoted && v.incumbent_score == 2.0));
+    assert!(decisions[0].challenger_score < 2.0);
+    assert_eq!(values, [1.0, 1.0]);
+}
+
+#[test]
+fn test_run_should_continue_from_rejected_challenger() {
+    let mut values = [1.0, 1.0];
+    let mut function = |x: &[f64]| (x[0] - 0.3).powi(2) + 10.0*(x[1] + 0.7).powi(2);
+    let decisions = SequentialTuner::new()
+        .stages_count(2)
+        .stage_function_calls_count(8)
+        .margin(100.0)
+        .run(&mut Newuoa::new(), &mut values, &mut function.clone(), &mut function)
+        .unwrap();
+    assert!(decisions.iter().all(|v| !v.promoted));
+    assert!(decisions[1].challenger_score < decisions[0].challenger_score);
+    assert_eq!(values, [1.0, 1.0]);
+}
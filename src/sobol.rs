@@ -0,0 +1,86 @@
+/// Joe and Kuo primitive polynomials and initial direction numbers `(s, a, m)` for the
+/// dimensions after the first one.
+const DIRECTIONS: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+pub const MAX_DIMENSION: usize = DIRECTIONS.len() + 1;
+
+/// Sobol sequence in the unit cube generated in Gray code order, skipping the origin.
+#[derive(Clone, Debug)]
+pub struct Sobol {
+    directions: Vec<[u32; 32]>,
+    state: Vec<u32>,
+    index: u32,
+}
+
+impl Sobol {
+    pub fn new(dimension: usize) -> Sobol {
+        assert!(dimension <= MAX_DIMENSION);
+        let directions = (0..dimension)
+            .map(|d| {
+                let mut v = [0u32; 32];
+                if d == 0 {
+                    for (k, item) in v.iter_mut().enumerate() {
+                        *item = 1 << (31 - k);
+                    }
+                    return v;
+                }
+                let (s, a, m) = DIRECTIONS[d - 1];
+                let s = s as usize;
+                for k in 0..32 {
+                    v[k] = if k < s {
+                        m[k] << (31 - k)
+                    } else {
+                        let mut value = v[k - s] ^ (v[k - s] >> s);
+                        for l in 1..s {
+                            if (a >> (s - 1 - l)) & 1 == 1 {
+                                value ^= v[k - l];
+                            }
+                        }
+                        value
+                    };
+                }
+                v
+            })
+            .collect();
+        Sobol {directions, state: vec![0; dimension], index: 0}
+    }
+
+    pub fn next_point(&mut self) -> Vec<f64> {
+        let bit = self.index.trailing_ones() as usize;
+        self.index += 1;
+        for (state, v) in self.state.iter_mut().zip(self.directions.iter()) {
+            *state ^= v[bit];
+        }
+        self.state.iter().map(|v| *v as f64/4294967296.0).collect()
+    }
+}
+
+#[test]
+fn test_sobol_should_generate_known_points() {
+    let mut sobol = Sobol::new(3);
+    assert_eq!(sobol.next_point(), vec![0.5, 0.5, 0.5]);
+    assert_eq!(sobol.next_point(), vec![0.75, 0.25, 0.25]);
+    assert_eq!(sobol.next_point(), vec![0.25, 0.75, 0.75]);
+    assert_eq!(sobol.next_point(), vec![0.375, 0.375, 0.625]);
+}
@@ -0,0 +1,93 @@
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::task::{Context, Poll};
+use std::thread::{spawn, JoinHandle};
+
+use futures::Stream;
+
+use iterates::{Iterate, Iterates};
+use wakeup::Wakeup;
+
+/// `Stream` counterpart of `Iterates`. The objective is evaluated on a background thread,
+/// which wakes the polling task after every evaluation, so a slow objective never blocks
+/// the executor. Dropping the stream abandons the run once the current evaluation is done.
+pub struct IterateStream {
+    iterates: Option<Receiver<Iterate>>,
+    worker: Option<JoinHandle<()>>,
+    wakeup: Wakeup,
+}
+
+impl IterateStream {
+    pub fn new<F>(iterates: Iterates<F>) -> IterateStream
+            where F: FnMut(&[f64]) -> f64 + Send + 'static {
+        let (sender, receiver) = channel();
+        let wakeup = Wakeup::default();
+        let worker_wakeup = wakeup.clone();
+        let worker = spawn(move || {
+            for iterate in iterates {
+                if sender.send(iterate).is_err() {
+                    break;
+                }
+                worker_wakeup.wake();
+            }
+            drop(sender);
+            worker_wakeup.wake();
+        });
+        IterateStream {iterates: Some(receiver), worker: Some(worker), wakeup}
+    }
+
+    fn try_receive(&self) -> Result<Iterate, TryRecvError> {
+        self.iterates.as_ref().map_or(Err(TryRecvError::Disconnected), |v| v.try_recv())
+    }
+}
+
+impl Stream for IterateStream {
+    type Item = Iterate;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Iterate>> {
+        let this = self.get_mut();
+        let received = match this.try_receive() {
+            Err(TryRecvError::Empty) => {
+                this.wakeup.register(context.waker());
+                this.try_receive()
+            }
+            other => other,
+        };
+        match received {
+            Ok(iterate) => Poll::Ready(Some(iterate)),
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+        }
+    }
+}
+
+impl Drop for IterateStream {
+    fn drop(&mut self) {
+        self.iterates = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[test]
+fn test_stream_should_yield_iterates_on_single_threaded_executor() {
+    use futures::StreamExt;
+    use Newuoa;
+    let mut stream = Newuoa::new()
+        .max_function_calls_count(10)
+        .stream(&[1.0, 2.0], |x: &[f64]| {
+            ::std::thread::sleep(::std::time::Duration::from_millis(1));
+            x[0]*x[0] + x[1]*x[1]
+        })
+        .unwrap();
+    let mut iterates = Vec::new();
+    while let Some(iterate) = ::futures::executor::block_on(stream.next()) {
+        iterates.push(iterate);
+    }
+    assert_eq!(iterates.len(), 10);
+    assert_eq!(iterates[0].value, 5.0);
+    for pair in iterates.windows(2) {
+        assert!(pair[1].value <= pair[0].value);
+    }
+}
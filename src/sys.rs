@@ -0,0 +1,93 @@
+//! Raw bindings to the native routine, for callers managing the working space or the
+//! callback dispatch themselves. `Newuoa::perform_mut` is built on these.
+
+use std::os::raw::c_void;
+
+/// Callback receiving `Closure::data` and the `n` coordinates of the point to evaluate.
+pub type Function = extern "C" fn(data: *const c_void, n: i64, x: *const f64) -> f64;
+
+#[repr(C)]
+pub struct Closure {
+    pub data: *const c_void,
+    pub function: Function,
+}
+
+impl Closure {
+    /// Dispatches to `function`, which must outlive every use of the result. A panic of
+    /// `function` aborts the process as it cannot unwind through the native code.
+    pub fn new<F>(function: &F) -> Closure where F: Fn(&[f64]) -> f64 {
+        extern "C" fn wrap<F>(closure: *const c_void, n: i64, x: *const f64) -> f64
+                where F: Fn(&[f64]) -> f64 {
+            use std::slice::from_raw_parts;
+            let closure = closure as *const F;
+            unsafe { (*closure)(from_raw_parts(x, n as usize)) }
+        }
+        Closure {data: function as *const _ as *const c_void, function: wrap::<F>}
+    }
+}
+
+/// Callback receiving `ClosureMut::data` and the `n` coordinates of the point to evaluate.
+pub type FunctionMut = extern "C" fn(data: *mut c_void, n: i64, x: *const f64) -> f64;
+
+#[repr(C)]
+pub struct ClosureMut {
+    pub data: *mut c_void,
+    pub function: FunctionMut,
+}
+
+impl ClosureMut {
+    /// Dispatches to `function`, which must outlive every use of the result. A panic of
+    /// `function` aborts the process as it cannot unwind through the native code.
+    pub fn new<F>(function: &mut F) -> ClosureMut where F: FnMut(&[f64]) -> f64 {
+        extern "C" fn wrap<F>(closure: *mut c_void, n: i64, x: *const f64) -> f64
+                where F: FnMut(&[f64]) -> f64 {
+            use std::slice::from_raw_parts;
+            let closure = closure as *mut F;
+            unsafe { (*closure)(from_raw_parts(x, n as usize)) }
+        }
+        ClosureMut {data: &mut *function as *mut _ as *mut c_void, function: wrap::<F>}
+    }
+}
+
+// The routine is reentrant: its state lives in `w` and on the stack, so concurrent calls
+// are sound as long as each one has its own working space and point. This is what makes
+// `Newuoa` `Send` and lets parallel multi-start run one solver per thread.
+extern "C" {
+    /// Minimizes from the `n` values of `x`, which receive the final point, and returns its
+    /// value. `w` must hold at least `(npt + 13)*(npt + n) + 3*n*(n + 3)/2` values and `npt`
+    /// lie in `[n + 2, (n + 1)(n + 2)/2]`, neither is checked.
+    pub fn newuoa_closure(function: *mut ClosureMut, n: i64, npt: i64, x: *mut f64,
+        rhobeg: f64, rhoend: f64, maxfun: i64, w: *mut f64) -> f64;
+
+    /// `newuoa_closure` for a callback that does not mutate its data.
+    pub fn newuoa_closure_const(function: *const Closure, n: i64, npt: i64, x: *mut f64,
+        rhobeg: f64, rhoend: f64, maxfun: i64, w: *mut f64) -> f64;
+}
+
+#[test]
+fn test_newuoa_closure_should_minimize() {
+    let n = 2;
+    let npt = 5;
+    let mut x = [1.0, 1.0];
+    let mut w = vec![0.0; (npt + 13)*(npt + n) + 3*n*(n + 3)/2];
+    let mut function = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    let mut closure = ClosureMut::new(&mut function);
+    let value = unsafe {
+        newuoa_closure(&mut closure, n as i64, npt as i64, x.as_mut_ptr(), 0.5, 1e-6, 100, w.as_mut_ptr())
+    };
+    assert!(value < 2.0);
+}
+
+#[test]
+fn test_newuoa_closure_const_should_minimize() {
+    let n = 2;
+    let npt = 5;
+    let mut x = [1.0, 1.0];
+    let mut w = vec![0.0; (npt + 13)*(npt + n) + 3*n*(n + 3)/2];
+    let function = |x: &[f64]| x[0]*x[0] + x[1]*x[1];
+    let closure = Closure::new(&function);
+    let value = unsafe {
+        newuoa_closure_const(&closure, n as i64, npt as i64, x.as_mut_ptr(), 0.5, 1e-6, 100, w.as_mut_ptr())
+    };
+    assert!(value < 2.0);
+}
@@ -0,0 +1,175 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+/// How a parameter is mapped from the unconstrained space searched by the solver.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Transform {
+    Identity,
+    /// Strictly positive parameter optimized as its logarithm.
+    Log,
+    /// Parameter in the open interval `(lower, upper)` optimized through a logistic map.
+    Bounded(f64, f64),
+    /// Parameter in the closed interval `[lower, upper]` optimized through a sine map,
+    /// which unlike the logistic one can start on a bound.
+    Sine(f64, f64),
+}
+
+impl Transform {
+    /// Whether `to_internal` accepts the value.
+    pub fn contains(&self, value: f64) -> bool {
+        match *self {
+            Transform::Identity => true,
+            Transform::Log => value > 0.0,
+            Transform::Bounded(lower, upper) => value > lower && value < upper,
+            Transform::Sine(lower, upper) => value >= lower && value <= upper,
+        }
+    }
+
+    pub fn to_internal(&self, value: f64) -> f64 {
+        assert!(self.contains(value));
+        match *self {
+            Transform::Identity => value,
+            Transform::Log => value.ln(),
+            Transform::Bounded(lower, upper) => {
+                let t = (value - lower)/(upper - lower);
+                (t/(1.0 - t)).ln()
+            }
+            Transform::Sine(lower, upper) => (2.0*(value - lower)/(upper - lower) - 1.0).asin(),
+        }
+    }
+
+    pub fn from_internal(&self, value: f64) -> f64 {
+        match *self {
+            Transform::Identity => value,
+            Transform::Log => value.exp(),
+            Transform::Bounded(lower, upper) => lower + (upper - lower)/(1.0 + (-value).exp()),
+            Transform::Sine(lower, upper) => lower + (upper - lower)*(1.0 + value.sin())/2.0,
+        }
+    }
+}
+
+/// Coordinates optimized as their logarithm, for strictly positive parameters spanning
+/// orders of magnitude such as learning rates or regularization strengths.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogScale {
+    indices: Vec<usize>,
+}
+
+impl LogScale {
+    pub fn new(indices: &[usize]) -> LogScale {
+        LogScale {indices: indices.to_vec()}
+    }
+
+    pub fn transforms(&self, variables_count: usize) -> Vec<Transform> {
+        (0..variables_count)
+            .map(|i| if self.indices.contains(&i) { Transform::Log } else { Transform::Identity })
+            .collect()
+    }
+
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        perform_mut(solver, &self.transforms(values.len()), values, function)
+    }
+}
+
+/// Box constraints `lower <= x[i] <= upper` for every coordinate, enforced through
+/// `Transform::Sine` so the objective is never called outside the box.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bounded {
+    bounds: Vec<(f64, f64)>,
+}
+
+impl Bounded {
+    pub fn new(bounds: &[(f64, f64)]) -> Bounded {
+        assert!(bounds.iter().all(|&(lower, upper)| lower < upper));
+        Bounded {bounds: bounds.to_vec()}
+    }
+
+    pub fn transforms(&self) -> Vec<Transform> {
+        self.bounds.iter().map(|&(lower, upper)| Transform::Sine(lower, upper)).collect()
+    }
+
+    pub fn perform_mut<F>(&self, solver: &mut Newuoa, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        perform_mut(solver, &self.transforms(), values, function)
+    }
+}
+
+/// Runs `solver` on the internal coordinates of `values` and writes the solution back in
+/// real space. The result solution and history are in real space too, while observers and
+/// the scaling of the solver apply to internal coordinates.
+pub fn perform_mut<F>(solver: &mut Newuoa, transforms: &[Transform], values: &mut [f64],
+        function: &mut F) -> Result<OptimizationResult, NewuoaError>
+        where F: FnMut(&[f64]) -> f64 {
+    assert_eq!(transforms.len(), values.len());
+    let mut internal = values.iter().zip(transforms.iter()).map(|(v, t)| t.to_internal(*v))
+        .collect::<Vec<_>>();
+    let mut result = {
+        let mut point = Vec::with_capacity(values.len());
+        solver.perform_mut(&mut internal, &mut |x: &[f64]| {
+            from_internal(transforms, x, &mut point);
+            function(&point)
+        })?
+    };
+    from_internal(transforms, &internal, &mut result.solution);
+    values.copy_from_slice(&result.solution);
+    for evaluation in result.history.iter_mut() {
+        let point = evaluation.point.clone();
+        from_internal(transforms, &point, &mut evaluation.point);
+    }
+    Ok(result)
+}
+
+fn from_internal(transforms: &[Transform], x: &[f64], result: &mut Vec<f64>) {
+    result.clear();
+    result.extend(x.iter().zip(transforms.iter()).map(|(v, t)| t.from_internal(*v)));
+}
+
+#[test]
+fn test_transforms_should_round_trip() {
+    for &(transform, value) in [(Transform::Log, 0.3), (Transform::Bounded(-1.0, 2.0), 1.5), (Transform::Sine(-1.0, 2.0), 1.5)].iter() {
+        let restored = transform.from_internal(transform.to_internal(value));
+        assert!((restored - value).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_transforms_should_contain_their_domain() {
+    assert!(Transform::Identity.contains(-1.0));
+    assert!(!Transform::Log.contains(0.0));
+    assert!(!Transform::Bounded(0.0, 1.0).contains(1.0));
+    assert!(Transform::Sine(0.0, 1.0).contains(1.0));
+    assert!(!Transform::Sine(0.0, 1.0).contains(1.5));
+}
+
+#[test]
+fn test_log_scale_should_pass_positive_values() {
+    let mut values = [1.0, 1.0];
+    LogScale::new(&[0])
+        .perform_mut(Newuoa::new().max_function_calls_count(50), &mut values, &mut |x: &[f64]| {
+            assert!(x[0] > 0.0);
+            (x[0].ln() - 1.0).powi(2) + x[1]*x[1]
+        })
+        .unwrap();
+    assert!(values[0] > 1.0);
+}
+
+#[test]
+fn test_bounded_should_stay_in_box() {
+    let mut values = [0.0, 0.5];
+    let result = Bounded::new(&[(0.0, 1.0), (0.0, 1.0)])
+        .perform_mut(Newuoa::new().max_function_calls_count(50), &mut values, &mut |x: &[f64]| {
+            assert!(x.iter().all(|v| *v >= 0.0 && *v <= 1.0));
+            (x[0] - 2.0).powi(2) + (x[1] - 0.5).powi(2)
+        })
+        .unwrap();
+    assert!(values[0] > 0.5);
+    assert!(result.value < 4.0);
+}
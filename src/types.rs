@@ -0,0 +1,138 @@
+use std::convert::TryFrom;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use error::NewuoaError;
+
+/// Trust region radius, positive and finite.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Radius(f64);
+
+impl Radius {
+    pub fn new(value: f64) -> Option<Radius> {
+        if value > 0.0 && value.is_finite() { Some(Radius(value)) } else { None }
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for Radius {
+    type Error = NewuoaError;
+
+    fn try_from(value: f64) -> Result<Radius, NewuoaError> {
+        Radius::new(value).ok_or(NewuoaError::InvalidTrustRegionRadius {radius: value})
+    }
+}
+
+impl From<Radius> for f64 {
+    fn from(value: Radius) -> f64 {
+        value.0
+    }
+}
+
+/// Maximum number of objective function calls, at least one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Budget(usize);
+
+impl Budget {
+    pub fn new(value: usize) -> Option<Budget> {
+        if value >= 1 { Some(Budget(value)) } else { None }
+    }
+
+    pub fn get(self) -> usize {
+        self.0
+    }
+}
+
+impl TryFrom<usize> for Budget {
+    type Error = NewuoaError;
+
+    fn try_from(value: usize) -> Result<Budget, NewuoaError> {
+        Budget::new(value).ok_or(NewuoaError::InvalidBudget {max_function_calls_count: value})
+    }
+}
+
+impl From<Budget> for usize {
+    fn from(value: Budget) -> usize {
+        value.0
+    }
+}
+
+/// Number of interpolation conditions, at least four since NEWUOA needs no less than
+/// `n + 2` of them for `n >= 2` variables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Npt(usize);
+
+impl Npt {
+    pub fn new(value: usize) -> Option<Npt> {
+        if value >= 4 { Some(Npt(value)) } else { None }
+    }
+
+    pub fn get(self) -> usize {
+        self.0
+    }
+}
+
+/// The error has no upper bound as it depends on the variables count.
+impl TryFrom<usize> for Npt {
+    type Error = NewuoaError;
+
+    fn try_from(value: usize) -> Result<Npt, NewuoaError> {
+        Npt::new(value).ok_or(NewuoaError::InvalidInterpolationConditions {
+            number_of_interpolation_conditions: value,
+            min: 4,
+            max: usize::MAX,
+        })
+    }
+}
+
+impl From<Npt> for usize {
+    fn from(value: Npt) -> usize {
+        value.0
+    }
+}
+
+/// What to do when the objective returns NaN or an infinity. Huge replacements such as
+/// `f64::MAX` overflow the quadratic model of the native routine, so penalties must be
+/// finite values of the objective's magnitude.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NonFinitePolicy {
+    /// Replaces the value with the worst finite one so far plus the spread between the
+    /// worst and the best one, at least one, or with zero before any finite value.
+    #[default]
+    WorstValue,
+    /// Replaces the value with the given finite one.
+    Penalty(f64),
+    /// Stops the run with `TerminationReason::NonFiniteObjective`.
+    Abort,
+}
+
+/// Progress reports printed to stderr during a run, like the `IPRINT` argument of
+/// Powell's Fortran code. Every level includes the ones before it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Verbosity {
+    #[default]
+    Silent,
+    /// The termination reason, evaluations count, best value and radius.
+    Summary,
+    /// The best value and evaluations count at every change of the radius estimate.
+    PerRho,
+    PerEval,
+}
+
+#[test]
+fn test_constructors_should_reject_invalid_values() {
+    assert_eq!(Radius::new(-1.0), None);
+    assert_eq!(Radius::new(0.0), None);
+    assert_eq!(Radius::new(f64::INFINITY), None);
+    assert_eq!(Radius::new(0.5).map(Radius::get), Some(0.5));
+    assert_eq!(Budget::new(0), None);
+    assert_eq!(Npt::new(3), None);
+    assert_eq!(Npt::try_from(5).map(Npt::get), Ok(5));
+    assert_eq!(Radius::try_from(0.0), Err(NewuoaError::InvalidTrustRegionRadius {radius: 0.0}));
+}
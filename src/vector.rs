@@ -0,0 +1,41 @@
+use nalgebra::{DVector, DVectorView, SVector};
+
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+impl Newuoa {
+    /// `perform_mut` for nalgebra users, with the variables count set to the vector length.
+    /// The objective gets a view of the native point without copying.
+    pub fn perform_dvector<F>(&mut self, values: &mut DVector<f64>, function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(DVectorView<f64>) -> f64 {
+        self.variables_count(values.len())
+            .perform_mut(values.as_mut_slice(), &mut |x: &[f64]| function(DVectorView::from_slice(x, x.len())))
+    }
+
+    /// `perform_mut` for fixed-size vectors, with the variables count set to `N`.
+    pub fn perform_svector<F, const N: usize>(&mut self, values: &mut SVector<f64, N>, function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&SVector<f64, N>) -> f64 {
+        self.variables_count(N)
+            .perform_mut(values.as_mut_slice(), &mut |x: &[f64]| function(&SVector::from_column_slice(x)))
+    }
+}
+
+#[test]
+fn test_perform_vectors_should_write_solution_back() {
+    let mut dynamic = DVector::from_vec(vec![1.0, 1.0, 1.0]);
+    let result = Newuoa::new()
+        .number_of_interpolation_conditions(7)
+        .max_function_calls_count(20)
+        .perform_dvector(&mut dynamic, &mut |x: DVectorView<f64>| x[0]*x[0] + x[1]*x[1] + x[2]*x[2])
+        .unwrap();
+    assert_eq!(dynamic.as_slice(), &result.solution[..]);
+    let mut fixed = SVector::<f64, 2>::from_column_slice(&[1.0, 1.0]);
+    let result = Newuoa::new()
+        .max_function_calls_count(20)
+        .perform_svector(&mut fixed, &mut |x: &SVector<f64, 2>| x[0]*x[0] + x[1]*x[1])
+        .unwrap();
+    assert_eq!(fixed.as_slice(), &result.solution[..]);
+}
@@ -0,0 +1,24 @@
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
+/// Waker of the task waiting for a background thread, woken by that thread once it has
+/// sent something or finished.
+#[derive(Clone, Default)]
+pub struct Wakeup {
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Wakeup {
+    pub fn register(&self, waker: &Waker) {
+        let mut current = self.waker.lock().unwrap_or_else(|e| e.into_inner());
+        if !current.as_ref().is_some_and(|v| v.will_wake(waker)) {
+            *current = Some(waker.clone());
+        }
+    }
+
+    pub fn wake(&self) {
+        if let Some(waker) = self.waker.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            waker.wake();
+        }
+    }
+}
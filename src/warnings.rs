@@ -0,0 +1,75 @@
+use std::fmt;
+
+use metadata::Settings;
+
+/// Configuration that is valid but likely to waste a run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// More than twice Powell's recommended `2n + 1` interpolation conditions, which
+    /// makes every iteration much more expensive for little gain.
+    LargeInterpolationConditions {number_of_interpolation_conditions: usize, variables_count: usize},
+    /// Fewer function calls than needed to build the initial model and take a step.
+    SmallBudget {max_function_calls_count: usize, number_of_interpolation_conditions: usize},
+    /// Initial and final radii more than twelve orders of magnitude apart.
+    WideRadiusRange {initial_trust_region_radius: f64, final_trust_region_radius: f64},
+}
+
+impl Warning {
+    pub fn check(settings: &Settings) -> Vec<Warning> {
+        let mut result = Vec::new();
+        let n = settings.variables_count;
+        let npt = settings.number_of_interpolation_conditions;
+        if npt > 2*(2*n + 1) {
+            result.push(Warning::LargeInterpolationConditions {
+                number_of_interpolation_conditions: npt,
+                variables_count: n,
+            });
+        }
+        if settings.max_function_calls_count < npt + 2 {
+            result.push(Warning::SmallBudget {
+                max_function_calls_count: settings.max_function_calls_count,
+                number_of_interpolation_conditions: npt,
+            });
+        }
+        if settings.initial_trust_region_radius > 1e12*settings.final_trust_region_radius {
+            result.push(Warning::WideRadiusRange {
+                initial_trust_region_radius: settings.initial_trust_region_radius,
+                final_trust_region_radius: settings.final_trust_region_radius,
+            });
+        }
+        result
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::LargeInterpolationConditions {number_of_interpolation_conditions, variables_count} =>
+                write!(formatter, "{} interpolation conditions for {} variables is unusually large, \
+                    {} is recommended", number_of_interpolation_conditions, variables_count,
+                    2*variables_count + 1),
+            Warning::SmallBudget {max_function_calls_count, number_of_interpolation_conditions} =>
+                write!(formatter, "max function calls count {} is less than {} needed to build \
+                    the initial model and take a step", max_function_calls_count,
+                    number_of_interpolation_conditions + 2),
+            Warning::WideRadiusRange {initial_trust_region_radius, final_trust_region_radius} =>
+                write!(formatter, "trust region radius range from {} to {} spans more than 12 \
+                    orders of magnitude", initial_trust_region_radius, final_trust_region_radius),
+        }
+    }
+}
+
+#[test]
+fn test_check_should_report_small_budget() {
+    let settings = Settings {
+        variables_count: 2,
+        number_of_interpolation_conditions: 5,
+        initial_trust_region_radius: 1.0,
+        final_trust_region_radius: 1e-6,
+        max_function_calls_count: 6,
+    };
+    assert_eq!(Warning::check(&settings), vec![Warning::SmallBudget {
+        max_function_calls_count: 6,
+        number_of_interpolation_conditions: 5,
+    }]);
+}
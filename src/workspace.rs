@@ -0,0 +1,47 @@
+use Newuoa;
+use error::NewuoaError;
+use result::OptimizationResult;
+
+/// Working spaces kept between runs of short-lived solvers, e.g. one `Newuoa` built per
+/// frame of a controller, so each run reuses a buffer instead of allocating one.
+#[derive(Clone, Debug, Default)]
+pub struct WorkspacePool {
+    buffers: Vec<Vec<f64>>,
+}
+
+impl WorkspacePool {
+    pub fn new() -> WorkspacePool {
+        WorkspacePool {buffers: Vec::new()}
+    }
+
+    /// Runs `solver` with a buffer of the pool, which gets it back afterwards.
+    pub fn perform_mut<F>(&mut self, solver: &mut Newuoa, values: &mut [f64], function: &mut F)
+            -> Result<OptimizationResult, NewuoaError>
+            where F: FnMut(&[f64]) -> f64 {
+        solver.working_space(self.buffers.pop().unwrap_or_default());
+        let result = solver.perform_mut(values, function);
+        self.buffers.push(solver.take_working_space());
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
+
+#[test]
+fn test_workspace_pool_should_reuse_buffer() {
+    let mut pool = WorkspacePool::new();
+    for _ in 0..3 {
+        let mut solver = Newuoa::new();
+        solver.max_function_calls_count(10);
+        pool.perform_mut(&mut solver, &mut [1.0, 1.0], &mut |x: &[f64]| x[0]*x[0] + x[1]*x[1])
+            .unwrap();
+        assert!(solver.take_working_space().is_empty());
+    }
+    assert_eq!(pool.len(), 1);
+}